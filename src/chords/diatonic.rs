@@ -0,0 +1,194 @@
+use std::fmt;
+
+use crate::keys::Key;
+use crate::intervals::UnspecifiedInterval;
+use crate::scale::KeyScale;
+use super::{ChordTemplate, KeyChord};
+
+/*
+ * The quality of a triad or seventh chord. Chords built from other intervals have the quality Other.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChordQuality {
+	Major,
+	Minor,
+	Diminished,
+	Augmented,
+	MajorSeventh,
+	DominantSeventh,
+	MinorSeventh,
+	HalfDiminishedSeventh,
+	DiminishedSeventh,
+	MinorMajorSeventh,
+	AugmentedSeventh,
+	AugmentedMajorSeventh,
+	Other,
+}
+
+/*
+ * A chord built by stacking thirds on a degree of a scale.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiatonicChord {
+	pub degree: i32,
+	pub quality: ChordQuality,
+	pub chord: KeyChord,
+}
+
+const CHORD_QUALITIES: [ChordQuality; 12] = [
+	ChordQuality::Major,
+	ChordQuality::Minor,
+	ChordQuality::Diminished,
+	ChordQuality::Augmented,
+	ChordQuality::MajorSeventh,
+	ChordQuality::DominantSeventh,
+	ChordQuality::MinorSeventh,
+	ChordQuality::HalfDiminishedSeventh,
+	ChordQuality::DiminishedSeventh,
+	ChordQuality::MinorMajorSeventh,
+	ChordQuality::AugmentedSeventh,
+	ChordQuality::AugmentedMajorSeventh,
+];
+
+impl ChordQuality {
+	/*
+	 * Returns the chord template of this quality, or None for ChordQuality::Other.
+	 */
+	pub fn to_chord_template(self) -> Option<ChordTemplate> {
+		match self {
+			ChordQuality::Major => Some(ChordTemplate::major()),
+			ChordQuality::Minor => Some(ChordTemplate::minor()),
+			ChordQuality::Diminished => Some(ChordTemplate::diminished()),
+			ChordQuality::Augmented => Some(ChordTemplate::augmented()),
+			ChordQuality::MajorSeventh => Some(ChordTemplate::major_seventh()),
+			ChordQuality::DominantSeventh => Some(ChordTemplate::dominant_seventh()),
+			ChordQuality::MinorSeventh => Some(ChordTemplate::minor_seventh()),
+			ChordQuality::HalfDiminishedSeventh => Some(ChordTemplate::half_diminished_seventh()),
+			ChordQuality::DiminishedSeventh => Some(ChordTemplate::diminished_seventh()),
+			ChordQuality::MinorMajorSeventh => Some(ChordTemplate::minor_major_seventh()),
+			ChordQuality::AugmentedSeventh => Some(ChordTemplate::augmented_seventh()),
+			ChordQuality::AugmentedMajorSeventh => Some(ChordTemplate::augmented_major_seventh()),
+			ChordQuality::Other => None,
+		}
+	}
+
+	pub fn from_chord_template(chord_template: &ChordTemplate) -> ChordQuality {
+		*CHORD_QUALITIES.iter()
+			.find(|quality| quality.to_chord_template().as_ref() == Some(chord_template))
+			.unwrap_or(&ChordQuality::Other)
+	}
+
+	pub fn from_key_chord(chord: &KeyChord) -> ChordQuality {
+		let intervals = chord.keys.iter().map(|key| Key::to_interval(chord.root, *key)).collect();
+		ChordQuality::from_chord_template(&ChordTemplate { intervals })
+	}
+
+	pub fn is_seventh_chord(self) -> bool {
+		!matches!(self, ChordQuality::Major | ChordQuality::Minor | ChordQuality::Diminished | ChordQuality::Augmented | ChordQuality::Other)
+	}
+}
+
+impl KeyScale {
+	/*
+	 * Builds a chord with the given number of keys on the given degree by stacking thirds within the scale,
+	 * so every key of the chord lies two scale steps above the previous one.
+	 */
+	pub fn stacked_thirds(&self, degree: i32, num_keys: usize) -> KeyChord {
+		let steps = UnspecifiedInterval::third().value;
+		let keys: Vec<Key> = (0..num_keys as i32).map(|index| self.key_at(degree + index * steps)).collect();
+		KeyChord::from_keys(self.key_at(degree), &keys)
+	}
+
+	pub fn triad(&self, degree: i32) -> DiatonicChord {
+		let chord = self.stacked_thirds(degree, 3);
+		DiatonicChord { degree, quality: ChordQuality::from_key_chord(&chord), chord }
+	}
+
+	pub fn seventh_chord(&self, degree: i32) -> DiatonicChord {
+		let chord = self.stacked_thirds(degree, 4);
+		DiatonicChord { degree, quality: ChordQuality::from_key_chord(&chord), chord }
+	}
+
+	/*
+	 * Returns the triads on all degrees of the scale, starting with the keynote.
+	 */
+	pub fn triads(&self) -> Vec<DiatonicChord> {
+		(0..self.keys.len() as i32).map(|degree| self.triad(degree)).collect()
+	}
+
+	/*
+	 * Returns the seventh chords on all degrees of the scale, starting with the keynote.
+	 */
+	pub fn seventh_chords(&self) -> Vec<DiatonicChord> {
+		(0..self.keys.len() as i32).map(|degree| self.seventh_chord(degree)).collect()
+	}
+}
+
+impl fmt::Display for ChordQuality {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ChordQuality::Major => write!(f, "major"),
+			ChordQuality::Minor => write!(f, "minor"),
+			ChordQuality::Diminished => write!(f, "diminished"),
+			ChordQuality::Augmented => write!(f, "augmented"),
+			ChordQuality::MajorSeventh => write!(f, "major seventh"),
+			ChordQuality::DominantSeventh => write!(f, "dominant seventh"),
+			ChordQuality::MinorSeventh => write!(f, "minor seventh"),
+			ChordQuality::HalfDiminishedSeventh => write!(f, "half-diminished seventh"),
+			ChordQuality::DiminishedSeventh => write!(f, "diminished seventh"),
+			ChordQuality::MinorMajorSeventh => write!(f, "minor major seventh"),
+			ChordQuality::AugmentedSeventh => write!(f, "augmented seventh"),
+			ChordQuality::AugmentedMajorSeventh => write!(f, "augmented major seventh"),
+			ChordQuality::Other => write!(f, "other"),
+		}
+	}
+}
+
+/*
+ * Writes the chord symbol of the chord, like "Bm7b5".
+ */
+impl fmt::Display for DiatonicChord {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.chord.fmt(f)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::scale::ScaleTemplate;
+
+	fn qualities(chords: &[DiatonicChord]) -> Vec<ChordQuality> {
+		chords.iter().map(|chord| chord.quality).collect()
+	}
+
+	#[test]
+	pub fn major_scale_chords() {
+		use ChordQuality::*;
+		let c_major = KeyScale::from_scale_template(&ScaleTemplate::major(), Key::c());
+		assert_eq!(qualities(&c_major.triads()), vec![Major, Minor, Minor, Major, Major, Minor, Diminished]);
+		assert_eq!(qualities(&c_major.seventh_chords()), vec![
+			MajorSeventh, MinorSeventh, MinorSeventh, MajorSeventh, DominantSeventh, MinorSeventh, HalfDiminishedSeventh]);
+
+		let dominant = c_major.seventh_chord(4);
+		assert_eq!(dominant.chord.keys, vec![Key::g(), Key::b(), Key::d(), Key::f()]);
+		assert_eq!(dominant.chord.root, Key::g());
+		assert_eq!(c_major.triad(7).chord, c_major.triad(0).chord);
+	}
+
+	#[test]
+	pub fn minor_scale_chords() {
+		use ChordQuality::*;
+		let a_minor = KeyScale::from_scale_template(&ScaleTemplate::minor(), Key::a());
+		assert_eq!(qualities(&a_minor.triads()), vec![Minor, Diminished, Major, Minor, Minor, Major, Major]);
+
+		let a_harmonic_minor = KeyScale::from_scale_template(&ScaleTemplate::minor_harmonic(), Key::a());
+		assert_eq!(qualities(&a_harmonic_minor.seventh_chords()), vec![
+			MinorMajorSeventh, HalfDiminishedSeventh, AugmentedMajorSeventh, MinorSeventh, DominantSeventh, MajorSeventh, DiminishedSeventh]);
+
+		let d_dorian = KeyScale::from_scale_template(&ScaleTemplate::dorian(), Key::d());
+		assert_eq!(d_dorian.triad(0).quality, Minor);
+		assert_eq!(d_dorian.triad(3).quality, Major);
+		assert_eq!(a_harmonic_minor.triad(2).to_string(), "Caug");
+	}
+}
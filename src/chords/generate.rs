@@ -1,6 +1,391 @@
 use crate::keys::Key;
+use crate::intervals::Interval;
+use crate::pitch::PitchRange;
+use crate::scale::KeyScale;
+use crate::key_signature::Mode;
+use crate::math::Random;
+use super::{KeyChord, PitchChord};
+use super::diatonic::ChordQuality;
+use super::roman_numeral::RomanNumeral;
+use super::voicing::free_voicings;
+use super::voice_leading::{VoiceLeadingRules, total_cost};
 
+/*
+ * The kinds of cadences. The authentic cadences resolve the dominant to the tonic: in a perfect authentic
+ * cadence both chords are in root position with the keynote in the highest voice, in an imperfect one the
+ * dominant is inverted. Plagal cadences resolve the subdominant to the tonic, half cadences end on the dominant,
+ * deceptive cadences lead the dominant to the sixth degree and Phrygian half cadences lead the minor
+ * subdominant in first inversion to the dominant.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CadenceType {
+	PerfectAuthentic,
+	ImperfectAuthentic,
+	Plagal,
+	Half,
+	Deceptive,
+	PhrygianHalf,
+}
+
+/*
+ * A cadence of the given type in a key and mode. The dominant is always a major chord, also in minor modes.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Cadence {
-    key: Key,
-    position: i8
-}
\ No newline at end of file
+	pub key: Key,
+	pub mode: Mode,
+	pub cadence_type: CadenceType,
+}
+
+impl Cadence {
+	pub fn new(key: Key, mode: Mode, cadence_type: CadenceType) -> Cadence {
+		Cadence { key, mode, cadence_type }
+	}
+
+	pub fn to_key_scale(self) -> KeyScale {
+		KeyScale::from_scale_template(&self.mode.to_scale_template(), self.key)
+	}
+
+	/*
+	 * Returns the chords of the cadence as roman numerals in the key of the cadence.
+	 */
+	pub fn roman_numerals(self) -> Vec<RomanNumeral> {
+		let scale = self.to_key_scale();
+		let diatonic_triad = |degree: i32, inversion: usize| RomanNumeral::new(degree, 0, scale.triad(degree).quality, inversion);
+		let dominant_seventh = RomanNumeral::new(4, 0, ChordQuality::DominantSeventh, 0);
+		let dominant = RomanNumeral::new(4, 0, ChordQuality::Major, 0);
+
+		match self.cadence_type {
+			CadenceType::PerfectAuthentic => vec![dominant_seventh, diatonic_triad(0, 0)],
+			CadenceType::ImperfectAuthentic => vec![RomanNumeral::new(4, 0, ChordQuality::Major, 1), diatonic_triad(0, 0)],
+			CadenceType::Plagal => vec![diatonic_triad(3, 0), diatonic_triad(0, 0)],
+			CadenceType::Half => vec![diatonic_triad(3, 0), dominant],
+			CadenceType::Deceptive => vec![dominant_seventh, diatonic_triad(5, 0)],
+			CadenceType::PhrygianHalf => vec![RomanNumeral::new(3, 0, ChordQuality::Minor, 1), dominant],
+		}
+	}
+
+	/*
+	 * Returns the chords of the cadence in order, ready to be appended to a progression.
+	 */
+	pub fn key_chords(self) -> Vec<KeyChord> {
+		let scale = self.to_key_scale();
+		self.roman_numerals().iter().filter_map(|roman_numeral| roman_numeral.to_key_chord(&scale)).collect()
+	}
+
+	/*
+	 * Voices the chords of the cadence with one voice per pitch range, so that the voices move as little as
+	 * possible while following the strict voice leading rules. Perfect authentic cadences end with the keynote
+	 * in the highest voice, imperfect authentic cadences with another key in the highest voice.
+	 * Returns None, if the chords can not be voiced in the ranges.
+	 */
+	pub fn pitch_chords(self, ranges: &[PitchRange]) -> Option<Vec<PitchChord>> {
+		let chords = self.key_chords();
+		let keynote = self.key;
+		let has_valid_soprano = |voicings: &[PitchChord]| {
+			let soprano = voicings[voicings.len() - 1].pitches.last().map(|pitch| Key::from(*pitch));
+			match self.cadence_type {
+				CadenceType::PerfectAuthentic => soprano == Some(keynote),
+				CadenceType::ImperfectAuthentic => soprano != Some(keynote),
+				_ => true,
+			}
+		};
+		free_voicings(&chords[0], ranges).into_iter()
+			.filter_map(|pitches| PitchChord { pitches }.lead_voices(&chords, ranges, VoiceLeadingRules::strict()))
+			.filter(|voicings| has_valid_soprano(voicings))
+			.min_by_key(|voicings| total_cost(voicings, &chords, VoiceLeadingRules::strict()))
+	}
+}
+
+/*
+ * The harmonic function of a chord: tonic (I, iii, vi), predominant (ii, IV) or dominant (V, vii°).
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HarmonicFunction {
+	Tonic,
+	Predominant,
+	Dominant,
+}
+
+impl HarmonicFunction {
+	pub fn from_degree(degree: i32) -> HarmonicFunction {
+		match degree.rem_euclid(7) {
+			1 | 3 => HarmonicFunction::Predominant,
+			4 | 6 => HarmonicFunction::Dominant,
+			_ => HarmonicFunction::Tonic,
+		}
+	}
+}
+
+/*
+ * The weights of the transitions between the degrees of a key, PROGRESSION_WEIGHTS[from][to].
+ * The weights follow the order tonic, predominant, dominant, tonic; a dominant never moves to a predominant.
+ */
+const PROGRESSION_WEIGHTS: [[u32; 7]; 7] = [
+	[0, 2, 1, 3, 2, 2, 1],
+	[0, 0, 0, 0, 4, 0, 2],
+	[0, 0, 0, 2, 0, 3, 0],
+	[2, 2, 0, 0, 3, 0, 1],
+	[4, 0, 0, 0, 0, 2, 0],
+	[0, 3, 1, 3, 1, 0, 0],
+	[3, 0, 0, 0, 1, 0, 0],
+];
+
+/*
+ * Generates chord progressions in a key by walking the weighted transitions between the degrees of the key,
+ * starting on the tonic (or another allowed degree with tonic function) and ending with the cadence (if any).
+ * Only the allowed degrees are used for the chords before the cadence; allowed degrees outside of 0..7 are
+ * ignored. The same seed always generates the same progression.
+ * In minor modes the dominant is major and the chord on the seventh degree is built on the leading tone.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgressionGenerator {
+	pub key: Key,
+	pub mode: Mode,
+	pub length: usize,
+	pub cadence: Option<CadenceType>,
+	pub allowed_degrees: Vec<i32>,
+	pub seventh_chords: bool,
+	pub seed: u64,
+}
+
+impl ProgressionGenerator {
+	/*
+	 * A generator for progressions of triads on all degrees, which end with a perfect authentic cadence.
+	 */
+	pub fn new(key: Key, mode: Mode, length: usize, seed: u64) -> ProgressionGenerator {
+		ProgressionGenerator {
+			key,
+			mode,
+			length,
+			cadence: Some(CadenceType::PerfectAuthentic),
+			allowed_degrees: (0..7).collect(),
+			seventh_chords: false,
+			seed,
+		}
+	}
+
+	fn roman_numeral(&self, scale: &KeyScale, degree: i32) -> RomanNumeral {
+		let quality = match (degree, self.seventh_chords) {
+			(4, false) => ChordQuality::Major,
+			(4, true) => ChordQuality::DominantSeventh,
+			(6, false) if scale.key_at(6) + Interval::minor_second() != scale.keynote() => ChordQuality::Diminished,
+			(6, true) if scale.key_at(6) + Interval::minor_second() != scale.keynote() => ChordQuality::DiminishedSeventh,
+			(_, false) => scale.triad(degree).quality,
+			(_, true) => scale.seventh_chord(degree).quality,
+		};
+		RomanNumeral::new(degree, 0, quality, 0)
+	}
+
+	/*
+	 * Returns the allowed degrees, which are degrees of the key.
+	 */
+	fn valid_allowed_degrees(&self) -> Vec<i32> {
+		self.allowed_degrees.iter().cloned().filter(|degree| (0..7).contains(degree)).collect()
+	}
+
+	/*
+	 * Returns the degree the progression starts on: the tonic, or another allowed degree with tonic function
+	 * (vi before iii), if the tonic is not allowed.
+	 */
+	fn first_degree(&self, allowed_degrees: &[i32], random: &mut Random) -> i32 {
+		match [0, 5, 2].iter().find(|degree| allowed_degrees.contains(degree)) {
+			Some(degree) => *degree,
+			None if !allowed_degrees.is_empty() => allowed_degrees[random.next_below(allowed_degrees.len() as u32) as usize],
+			None => 0,
+		}
+	}
+
+	/*
+	 * Returns the degrees of the chords before the cadence. The last of them is chosen so that it can move
+	 * to the first chord of the cadence.
+	 */
+	fn degrees(&self, num_degrees: usize, cadence_degree: Option<i32>) -> Vec<i32> {
+		let mut random = Random::new(self.seed);
+		let allowed_degrees = self.valid_allowed_degrees();
+		let mut degrees: Vec<i32> = Vec::new();
+		for index in 0..num_degrees {
+			let degree = match degrees.last() {
+				None => self.first_degree(&allowed_degrees, &mut random),
+				Some(previous) => {
+					let is_last = index + 1 == num_degrees;
+					let weights: Vec<u32> = (0..7).map(|degree| {
+						if !allowed_degrees.contains(&degree) {
+							return 0;
+						}
+						let weight = PROGRESSION_WEIGHTS[*previous as usize][degree as usize];
+						match cadence_degree {
+							Some(cadence_degree) if is_last => weight * PROGRESSION_WEIGHTS[degree as usize][cadence_degree as usize],
+							_ => weight,
+						}
+					}).collect();
+					match random.choose_weighted(&weights) {
+						Some(degree) => degree as i32,
+						None => {
+							// the allowed degrees do not continue the progression, so any allowed degree is used,
+							// as long as a dominant does not move to a predominant
+							let is_dominant = HarmonicFunction::from_degree(*previous) == HarmonicFunction::Dominant;
+							let candidates: Vec<i32> = allowed_degrees.iter().cloned()
+								.filter(|degree| !is_dominant || HarmonicFunction::from_degree(*degree) != HarmonicFunction::Predominant)
+								.collect();
+							if candidates.is_empty() {
+								*previous
+							} else {
+								candidates[random.next_below(candidates.len() as u32) as usize]
+							}
+						},
+					}
+				},
+			};
+			degrees.push(degree);
+		}
+		degrees
+	}
+
+	/*
+	 * Generates the progression as roman numerals in the key of the generator.
+	 */
+	pub fn roman_numerals(&self) -> Vec<RomanNumeral> {
+		let scale = KeyScale::from_scale_template(&self.mode.to_scale_template(), self.key);
+		let cadence = self.cadence.map(|cadence_type| Cadence::new(self.key, self.mode, cadence_type).roman_numerals()).unwrap_or_default();
+		if cadence.len() >= self.length {
+			return cadence[cadence.len() - self.length..].to_vec();
+		}
+
+		let cadence_degree = cadence.first().map(|roman_numeral| roman_numeral.degree);
+		let mut roman_numerals: Vec<RomanNumeral> = self.degrees(self.length - cadence.len(), cadence_degree).iter()
+			.map(|degree| self.roman_numeral(&scale, *degree))
+			.collect();
+		roman_numerals.extend(cadence);
+		roman_numerals
+	}
+
+	/*
+	 * Generates the progression as chords.
+	 */
+	pub fn key_chords(&self) -> Vec<KeyChord> {
+		let scale = KeyScale::from_scale_template(&self.mode.to_scale_template(), self.key);
+		self.roman_numerals().iter().filter_map(|roman_numeral| roman_numeral.to_key_chord(&scale)).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::chords::voicing::satb_ranges;
+
+	fn keys(s: &str) -> Vec<Key> {
+		s.split_whitespace().map(|key| key.parse().unwrap()).collect()
+	}
+
+	#[test]
+	pub fn cadence_chords() {
+		let chords = Cadence::new(Key::c(), Mode::Major, CadenceType::PerfectAuthentic).key_chords();
+		assert_eq!(chords, vec![
+			KeyChord::from_keys(Key::g(), &keys("g b d f")),
+			KeyChord::from_keys(Key::c(), &keys("c e g")),
+		]);
+
+		let chords = Cadence::new(Key::a(), Mode::Minor, CadenceType::PerfectAuthentic).key_chords();
+		assert_eq!(chords[0].keys, keys("e g# b d"));
+		assert_eq!(chords[1].keys, keys("a c e"));
+
+		let chords = Cadence::new(Key::c(), Mode::Major, CadenceType::ImperfectAuthentic).key_chords();
+		assert_eq!(chords[0].bass(), Key::b());
+		assert_eq!(Cadence::new(Key::c(), Mode::Major, CadenceType::Plagal).key_chords()[0].keys, keys("f a c"));
+		assert_eq!(Cadence::new(Key::a(), Mode::Minor, CadenceType::Half).key_chords()[0].keys, keys("d f a"));
+		assert_eq!(Cadence::new(Key::a(), Mode::Minor, CadenceType::Half).key_chords()[1].keys, keys("e g# b"));
+		assert_eq!(Cadence::new(Key::c(), Mode::Major, CadenceType::Deceptive).key_chords()[1].keys, keys("a c e"));
+		assert_eq!(Cadence::new(Key::a(), Mode::Minor, CadenceType::Deceptive).key_chords()[1].keys, keys("f a c"));
+
+		let chords = Cadence::new(Key::a(), Mode::Minor, CadenceType::PhrygianHalf).key_chords();
+		assert_eq!(chords[0].keys, keys("f a d"));
+		assert_eq!(chords[1].keys, keys("e g# b"));
+
+		let chords = Cadence::new(Key::d(), Mode::Dorian, CadenceType::PerfectAuthentic).key_chords();
+		assert_eq!(chords[0].keys, keys("a c# e g"));
+		assert_eq!(chords[1].keys, keys("d f a"));
+	}
+
+	#[test]
+	pub fn voiced_cadences() {
+		let voicings = Cadence::new(Key::c(), Mode::Major, CadenceType::PerfectAuthentic).pitch_chords(&satb_ranges()).unwrap();
+		assert_eq!(voicings.len(), 2);
+		assert_eq!(Key::from(voicings[0].pitches[0]), Key::g());
+		assert_eq!(Key::from(voicings[1].pitches[0]), Key::c());
+		assert_eq!(Key::from(voicings[1].pitches[3]), Key::c());
+
+		let voicings = Cadence::new(Key::f(), Mode::Major, CadenceType::ImperfectAuthentic).pitch_chords(&satb_ranges()).unwrap();
+		assert_ne!(Key::from(voicings[1].pitches[3]), Key::f());
+	}
+
+	#[test]
+	pub fn generate_progressions() {
+		let generator = ProgressionGenerator::new(Key::c(), Mode::Major, 8, 7);
+		let progression = generator.roman_numerals();
+		assert_eq!(progression.len(), 8);
+		assert_eq!(progression, generator.roman_numerals());
+		assert_eq!(progression[0].to_string(), "I");
+		assert_eq!(progression[6].to_string(), "V7");
+		assert_eq!(progression[7].to_string(), "I");
+		assert_eq!(generator.key_chords().len(), 8);
+
+		for seed in 0..50 {
+			let generator = ProgressionGenerator { cadence: Some(CadenceType::Half), ..ProgressionGenerator::new(Key::a(), Mode::Minor, 6, seed) };
+			let progression = generator.roman_numerals();
+			for pair in progression[..4].windows(2) {
+				let functions = (HarmonicFunction::from_degree(pair[0].degree), HarmonicFunction::from_degree(pair[1].degree));
+				assert_ne!(functions, (HarmonicFunction::Dominant, HarmonicFunction::Predominant));
+			}
+			assert_eq!(progression[5].to_string(), "V");
+		}
+	}
+
+	#[test]
+	pub fn generate_progressions_from_allowed_degrees() {
+		let generator = ProgressionGenerator {
+			allowed_degrees: vec![0, 3, 4],
+			seventh_chords: true,
+			cadence: None,
+			..ProgressionGenerator::new(Key::g(), Mode::Major, 12, 3)
+		};
+		let progression = generator.roman_numerals();
+		assert_eq!(progression.len(), 12);
+		assert!(progression.iter().all(|roman_numeral| [0, 3, 4].contains(&roman_numeral.degree)));
+		assert!(progression.iter().all(|roman_numeral| roman_numeral.quality.is_seventh_chord()));
+
+		let other_seed = ProgressionGenerator { seed: 4, ..generator.clone() };
+		let seeds_differ = (0..20).any(|seed| ProgressionGenerator { seed, ..generator.clone() }.roman_numerals() != other_seed.roman_numerals());
+		assert!(seeds_differ);
+
+		// without the tonic the progression starts on another tonic function degree
+		for seed in 0..50 {
+			for allowed_degrees in [vec![1, 4, 5], vec![4, 1]].iter() {
+				let generator = ProgressionGenerator {
+					allowed_degrees: allowed_degrees.clone(),
+					cadence: None,
+					..ProgressionGenerator::new(Key::c(), Mode::Major, 8, seed)
+				};
+				let degrees: Vec<i32> = generator.roman_numerals().iter().map(|roman_numeral| roman_numeral.degree).collect();
+				assert!(degrees.iter().all(|degree| allowed_degrees.contains(degree)));
+				// even when the weights of all allowed degrees are 0, a dominant does not move to a predominant
+				for pair in degrees.windows(2) {
+					let functions = (HarmonicFunction::from_degree(pair[0]), HarmonicFunction::from_degree(pair[1]));
+					assert_ne!(functions, (HarmonicFunction::Dominant, HarmonicFunction::Predominant));
+				}
+			}
+			let generator = ProgressionGenerator { allowed_degrees: vec![1, 4, 5], ..ProgressionGenerator::new(Key::c(), Mode::Major, 8, seed) };
+			assert_eq!(generator.roman_numerals()[0].degree, 5);
+		}
+
+		// degrees outside of the key are ignored
+		let generator = ProgressionGenerator { allowed_degrees: vec![-1, 7, 3, 4, 12], ..ProgressionGenerator::new(Key::c(), Mode::Major, 8, 5) };
+		assert!(generator.roman_numerals().iter().all(|roman_numeral| (0..7).contains(&roman_numeral.degree)));
+		let generator = ProgressionGenerator { allowed_degrees: vec![7, -1], cadence: None, ..ProgressionGenerator::new(Key::c(), Mode::Major, 4, 5) };
+		assert!(generator.roman_numerals().iter().all(|roman_numeral| roman_numeral.degree == 0));
+
+		let short = ProgressionGenerator::new(Key::c(), Mode::Major, 1, 0).roman_numerals();
+		assert_eq!(short.len(), 1);
+		assert_eq!(short[0].to_string(), "I");
+	}
+}
@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::keys::Key;
+use crate::intervals::Interval;
+use crate::spelled_key::SpelledKey;
+use super::{ChordTemplate, KeyChord, PitchChord};
+
+/*
+ * One possible name of a set of keys: a chord template on a root with the tones of the template
+ * missing in the keys and the keys which are not part of the template.
+ * inversion is 0 for root position, 1 if the bass is the second tone of the template and so on.
+ * It is None, if the bass is unknown or not part of the template.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChordCandidate {
+	pub root: Key,
+	pub symbol: &'static str,
+	pub chord_template: ChordTemplate,
+	pub bass: Option<Key>,
+	pub inversion: Option<usize>,
+	pub missing: Vec<Interval>,
+	pub added: Vec<Interval>,
+}
+
+const MAX_COST: u32 = 4;
+
+impl ChordCandidate {
+	/*
+	 * How badly the template fits the keys. A missing fifth costs 1, every other missing or added tone costs 2.
+	 */
+	pub fn cost(&self) -> u32 {
+		let missing_cost: u32 = self.missing.iter().map(|interval| if *interval == Interval::fifth() { 1 } else { 2 }).sum();
+		missing_cost + 2 * self.added.len() as u32
+	}
+}
+
+/*
+ * Returns all chord templates of ChordTemplate::all() on every key of the chord, which fit the keys,
+ * ordered from the best to the worst fit. Candidates in root position are preferred over inversions.
+ */
+fn identify(keys: &[Key], bass: Option<Key>) -> Vec<ChordCandidate> {
+	let mut candidates: Vec<(usize, ChordCandidate)> = Vec::new();
+	let mut roots: Vec<Key> = keys.to_vec();
+	roots.sort_by_key(|key| key.get_value());
+	// doubled keys are the same root
+	roots.dedup();
+
+	for root in roots {
+		let chord_intervals: HashSet<Interval> = keys.iter().map(|key| Key::to_interval(root, *key)).collect();
+		for (template_index, (symbol, chord_template)) in ChordTemplate::all().into_iter().enumerate() {
+			let mut template_intervals: Vec<Interval> = chord_template.intervals.iter().map(|interval| interval.to_base_interval()).collect();
+			template_intervals.sort_by_key(|interval| interval.halftones);
+			template_intervals.dedup();
+
+			let mut missing: Vec<Interval> = template_intervals.iter().filter(|interval| !chord_intervals.contains(interval)).cloned().collect();
+			let mut added: Vec<Interval> = chord_intervals.iter().filter(|interval| !template_intervals.contains(interval)).cloned().collect();
+			missing.sort_by_key(|interval| interval.halftones);
+			added.sort_by_key(|interval| interval.halftones);
+
+			// a power chord with added tones is always better described by another template
+			if !added.is_empty() && template_intervals.len() < 3 {
+				continue;
+			}
+
+			// the inversion counts the tones in the order of the template, so the ninth comes after the seventh
+			let mut ordered_intervals: Vec<Interval> = chord_template.intervals.iter().cloned().collect();
+			ordered_intervals.sort_by_key(|interval| interval.halftones);
+			let inversion = bass.and_then(|bass| ordered_intervals.iter().position(|interval| interval.to_base_interval() == Key::to_interval(root, bass)));
+			let candidate = ChordCandidate { root, symbol, chord_template, bass, inversion, missing, added };
+			if candidate.cost() <= MAX_COST {
+				candidates.push((template_index, candidate));
+			}
+		}
+	}
+
+	candidates.sort_by_key(|(template_index, candidate)| (candidate.cost(), candidate.inversion.map_or(1, |inversion| inversion.min(1)), *template_index));
+	candidates.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+impl KeyChord {
+	/*
+	 * Returns the possible names of this chord, ordered from the best to the worst fit.
+	 * The first key of the chord is used as bass to determine the inversion.
+	 */
+	pub fn identify(&self) -> Vec<ChordCandidate> {
+		identify(&self.keys, Some(self.bass()))
+	}
+}
+
+impl PitchChord {
+	/*
+	 * Returns the possible names of this chord, ordered from the best to the worst fit.
+	 * The lowest pitch of the chord is used as bass to determine the inversion.
+	 */
+	pub fn identify(&self) -> Vec<ChordCandidate> {
+		let keys: Vec<Key> = self.pitches.iter().map(|pitch| Key::from(*pitch)).collect();
+		identify(&keys, self.bass().map(Key::from))
+	}
+}
+
+/*
+ * Names added tones like extensions of the chord.
+ */
+fn added_tone_name(interval: Interval) -> &'static str {
+	match interval.halftones {
+		0 => "1",
+		1 => "b9",
+		2 => "9",
+		3 => "#9",
+		4 => "3",
+		5 => "11",
+		6 => "#11",
+		7 => "5",
+		8 => "b13",
+		9 => "13",
+		10 => "b7",
+		11 => "7",
+		_ => unimplemented!(),
+	}
+}
+
+/*
+ * Names missing tones by their degree in the chord, ignoring alterations.
+ */
+fn missing_tone_name(interval: Interval) -> &'static str {
+	match interval.to_unspecified_interval().value {
+		0 => "1",
+		1 => "9",
+		2 => "3",
+		3 => "11",
+		4 => "5",
+		5 => "13",
+		6 => "7",
+		_ => unimplemented!(),
+	}
+}
+
+/*
+ * Writes the candidate like "C7", "Am7/C", "C7(no5)" or "Cmaj7(add13)".
+ */
+impl fmt::Display for ChordCandidate {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let root = SpelledKey::from_key(self.root).to_string();
+		write!(f, "{}{}{}", root[..1].to_uppercase(), &root[1..], self.symbol)?;
+		let changes: Vec<String> = self.missing.iter().map(|interval| format!("no{}", missing_tone_name(*interval)))
+			.chain(self.added.iter().map(|interval| format!("add{}", added_tone_name(*interval))))
+			.collect();
+		if !changes.is_empty() {
+			write!(f, "({})", changes.join(","))?;
+		}
+		if let Some(bass) = self.bass {
+			if bass != self.root {
+				let bass = SpelledKey::from_key(bass).to_string();
+				write!(f, "/{}{}", bass[..1].to_uppercase(), &bass[1..])?;
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::pitch::Pitch;
+
+	fn key_chord(keys: &[Key]) -> KeyChord {
+		KeyChord::from_keys(keys[0], keys)
+	}
+
+	fn names(candidates: &[ChordCandidate]) -> Vec<String> {
+		candidates.iter().map(|candidate| candidate.to_string()).collect()
+	}
+
+	#[test]
+	pub fn identify_key_chords() {
+		let candidates = key_chord(&[Key::c(), Key::e(), Key::g(), Key::ais()]).identify();
+		assert_eq!(candidates[0].to_string(), "C7");
+		assert_eq!(candidates[0].cost(), 0);
+
+		let candidates = key_chord(&[Key::a(), Key::c(), Key::e(), Key::g()]).identify();
+		assert_eq!(names(&candidates[..2]), vec!["Am7", "C6/A"]);
+
+		let candidates = key_chord(&[Key::c(), Key::e(), Key::ais()]).identify();
+		assert_eq!(candidates[0].to_string(), "C7(no5)");
+		assert_eq!(candidates[0].missing, vec![Interval::fifth()]);
+
+		let candidates = key_chord(&[Key::c(), Key::e(), Key::g(), Key::d()]).identify();
+		assert_eq!(candidates[0].to_string(), "Cadd9");
+
+		let candidates = key_chord(&[Key::c(), Key::e(), Key::g(), Key::b(), Key::fis()]).identify();
+		assert_eq!(candidates[0].to_string(), "Cmaj7(add#11)");
+		assert_eq!(candidates[0].added, vec![Interval::tritone()]);
+
+		assert!(key_chord(&[Key::c(), Key::cis(), Key::d(), Key::dis(), Key::e()]).identify().is_empty());
+	}
+
+	#[test]
+	pub fn identify_pitch_chords() {
+		let pitches = [Pitch { value: 31 }, Pitch { value: 34 }, Pitch { value: 39 }];
+		let pitch_chord = PitchChord::from_pitches(&pitches);
+		let candidates = pitch_chord.identify();
+		assert_eq!(candidates[0].to_string(), "C/E");
+		assert_eq!(candidates[0].inversion, Some(1));
+
+		// with c in the bass the c sixth in root position is preferred over the inverted a minor seventh
+		let pitches = [Pitch { value: 39 }, Pitch { value: 43 }, Pitch { value: 46 }, Pitch { value: 48 }];
+		let pitch_chord = PitchChord::from_pitches(&pitches);
+		let candidates = pitch_chord.identify();
+		assert_eq!(names(&candidates[..2]), vec!["C6", "Am7/C"]);
+		assert_eq!(candidates[1].inversion, Some(1));
+
+		// e3 c4 g4 a#4 d5
+		let pitches = [Pitch { value: 31 }, Pitch { value: 39 }, Pitch { value: 46 }, Pitch { value: 49 }, Pitch { value: 53 }];
+		let candidates = PitchChord::from_pitches(&pitches).identify();
+		assert_eq!(candidates[0].to_string(), "C9/E");
+		assert_eq!(candidates[0].inversion, Some(1));
+
+		// c4 e4 g4 c5: the doubled root is named once
+		let pitches = [Pitch { value: 39 }, Pitch { value: 43 }, Pitch { value: 46 }, Pitch { value: 51 }];
+		let candidates = PitchChord::from_pitches(&pitches).identify();
+		assert_eq!(names(&candidates[..3]), vec!["C", "Cmaj7(no7)", "C7(no7)"]);
+		let mut all_names = names(&candidates);
+		all_names.dedup();
+		assert_eq!(all_names.len(), candidates.len());
+	}
+}
@@ -1,24 +1,44 @@
+pub mod diatonic;
 pub mod generate;
+pub mod identify;
+pub mod modulation;
+pub mod neo_riemannian;
+pub mod roman_numeral;
+pub mod symbol;
+pub mod voice_leading;
+pub mod voicing;
 
 use std::collections::HashSet;
 
 use crate::keys::Key;
-use crate::intervals::Interval;
+use crate::intervals::{Interval, UnspecifiedInterval};
 use crate::pitch::{Pitch, Octave, PitchRange};
+use crate::spelled_key::SpelledKey;
 
 /*
  * Represents a set of Intervals, which relative to a keynote build a chord.
  */
+#[derive(Clone, Debug, PartialEq)]
 pub struct ChordTemplate {
 	pub intervals: HashSet<Interval>,
 }
 
+/*
+ * The keys of a chord in voice order, starting with the bass, and the root the chord is built on.
+ * The bass does not have to be the root (inversions) or even a tone of the chord (slash chords like C/Bb).
+ */
+#[derive(Clone, Debug, PartialEq)]
 pub struct KeyChord {
-	pub keys: HashSet<Key>,
+	pub root: Key,
+	pub keys: Vec<Key>,
 }
 
+/*
+ * The pitches of a chord, ordered from the lowest to the highest pitch.
+ */
+#[derive(Clone, Debug, PartialEq)]
 pub struct PitchChord {
-	pub pitches: HashSet<Pitch>,
+	pub pitches: Vec<Pitch>,
 }
 
 impl ChordTemplate {
@@ -41,18 +61,315 @@ impl ChordTemplate {
 		chord_template.intervals.insert(Interval::fifth());
 		chord_template
 	}
+
+	pub fn from_intervals(intervals: &[Interval]) -> ChordTemplate {
+		ChordTemplate { intervals: intervals.iter().cloned().collect() }
+	}
+
+	fn from_halftones(halftones: &[i32]) -> ChordTemplate {
+		ChordTemplate { intervals: halftones.iter().map(|halftones| Interval { halftones: *halftones }).collect() }
+	}
+
+	pub fn power() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 7]) }
+
+	// triads
+	pub fn diminished() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 3, 6]) }
+	pub fn augmented() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 8]) }
+	pub fn suspended_second() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 2, 7]) }
+	pub fn suspended_fourth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 5, 7]) }
+
+	// seventh chords
+	pub fn major_seventh() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 7, 11]) }
+	pub fn dominant_seventh() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 7, 10]) }
+	pub fn minor_seventh() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 3, 7, 10]) }
+	pub fn half_diminished_seventh() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 3, 6, 10]) }
+	pub fn diminished_seventh() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 3, 6, 9]) }
+	pub fn minor_major_seventh() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 3, 7, 11]) }
+	pub fn augmented_seventh() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 8, 10]) }
+	pub fn augmented_major_seventh() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 8, 11]) }
+	pub fn dominant_seventh_suspended_fourth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 5, 7, 10]) }
+
+	// sixth and added tone chords
+	pub fn major_sixth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 7, 9]) }
+	pub fn minor_sixth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 3, 7, 9]) }
+	pub fn added_ninth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 7, 14]) }
+	pub fn minor_added_ninth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 3, 7, 14]) }
+
+	// extended chords
+	pub fn dominant_ninth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 7, 10, 14]) }
+	pub fn major_ninth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 7, 11, 14]) }
+	pub fn minor_ninth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 3, 7, 10, 14]) }
+	pub fn dominant_eleventh() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 7, 10, 14, 17]) }
+	pub fn minor_eleventh() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 3, 7, 10, 14, 17]) }
+	// the eleventh is left out of major and dominant thirteenth chords, as it clashes with the third
+	pub fn dominant_thirteenth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 7, 10, 14, 21]) }
+	pub fn major_thirteenth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 7, 11, 14, 21]) }
+	pub fn minor_thirteenth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 3, 7, 10, 14, 17, 21]) }
+
+	// altered dominants
+	pub fn dominant_seventh_flat_ninth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 7, 10, 13]) }
+	pub fn dominant_seventh_sharp_ninth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 7, 10, 15]) }
+	pub fn dominant_seventh_sharp_eleventh() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 7, 10, 18]) }
+	pub fn dominant_seventh_flat_thirteenth() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 7, 10, 20]) }
+	// root, third and seventh with all alterations of fifth and ninth
+	pub fn altered_dominant() -> ChordTemplate { ChordTemplate::from_halftones(&[0, 4, 6, 8, 10, 13, 15]) }
+
+	/*
+	 * Returns all chord templates with their symbols, which are understood by ChordTemplate::from_symbol.
+	 */
+	pub fn all() -> Vec<(&'static str, ChordTemplate)> {
+		CHORD_TEMPLATES.iter().map(|(symbol, chord_template)| (*symbol, chord_template())).collect()
+	}
+
+	/*
+	 * Looks up a chord template by its symbol without the root, like "m7", "maj7", "7b9" or "" for a major chord.
+	 * Common alternative symbols like "-7", "ø", "°7" or "Δ" are understood as well.
+	 */
+	pub fn from_symbol(symbol: &str) -> Option<ChordTemplate> {
+		let symbol = symbol.trim();
+		CHORD_TEMPLATES.iter()
+			.chain(CHORD_TEMPLATE_ALIASES.iter())
+			.find(|(template_symbol, _)| *template_symbol == symbol)
+			.map(|(_, chord_template)| chord_template())
+	}
+}
+
+type ChordTemplateConstructor = fn() -> ChordTemplate;
+
+const CHORD_TEMPLATES: &[(&str, ChordTemplateConstructor)] = &[
+	("", ChordTemplate::major),
+	("m", ChordTemplate::minor),
+	("dim", ChordTemplate::diminished),
+	("aug", ChordTemplate::augmented),
+	("sus2", ChordTemplate::suspended_second),
+	("sus4", ChordTemplate::suspended_fourth),
+	("5", ChordTemplate::power),
+	("maj7", ChordTemplate::major_seventh),
+	("7", ChordTemplate::dominant_seventh),
+	("m7", ChordTemplate::minor_seventh),
+	("m7b5", ChordTemplate::half_diminished_seventh),
+	("dim7", ChordTemplate::diminished_seventh),
+	("mMaj7", ChordTemplate::minor_major_seventh),
+	("aug7", ChordTemplate::augmented_seventh),
+	("maj7#5", ChordTemplate::augmented_major_seventh),
+	("7sus4", ChordTemplate::dominant_seventh_suspended_fourth),
+	("6", ChordTemplate::major_sixth),
+	("m6", ChordTemplate::minor_sixth),
+	("add9", ChordTemplate::added_ninth),
+	("madd9", ChordTemplate::minor_added_ninth),
+	("9", ChordTemplate::dominant_ninth),
+	("maj9", ChordTemplate::major_ninth),
+	("m9", ChordTemplate::minor_ninth),
+	("11", ChordTemplate::dominant_eleventh),
+	("m11", ChordTemplate::minor_eleventh),
+	("13", ChordTemplate::dominant_thirteenth),
+	("maj13", ChordTemplate::major_thirteenth),
+	("m13", ChordTemplate::minor_thirteenth),
+	("7b9", ChordTemplate::dominant_seventh_flat_ninth),
+	("7#9", ChordTemplate::dominant_seventh_sharp_ninth),
+	("7#11", ChordTemplate::dominant_seventh_sharp_eleventh),
+	("7b13", ChordTemplate::dominant_seventh_flat_thirteenth),
+	("7alt", ChordTemplate::altered_dominant),
+];
+
+const CHORD_TEMPLATE_ALIASES: &[(&str, ChordTemplateConstructor)] = &[
+	("M", ChordTemplate::major),
+	("maj", ChordTemplate::major),
+	("-", ChordTemplate::minor),
+	("min", ChordTemplate::minor),
+	("°", ChordTemplate::diminished),
+	("o", ChordTemplate::diminished),
+	("+", ChordTemplate::augmented),
+	("sus", ChordTemplate::suspended_fourth),
+	("M7", ChordTemplate::major_seventh),
+	("Δ", ChordTemplate::major_seventh),
+	("Δ7", ChordTemplate::major_seventh),
+	("-7", ChordTemplate::minor_seventh),
+	("min7", ChordTemplate::minor_seventh),
+	("ø", ChordTemplate::half_diminished_seventh),
+	("ø7", ChordTemplate::half_diminished_seventh),
+	("-7b5", ChordTemplate::half_diminished_seventh),
+	("°7", ChordTemplate::diminished_seventh),
+	("o7", ChordTemplate::diminished_seventh),
+	("mM7", ChordTemplate::minor_major_seventh),
+	("m(maj7)", ChordTemplate::minor_major_seventh),
+	("+7", ChordTemplate::augmented_seventh),
+	("+maj7", ChordTemplate::augmented_major_seventh),
+	("-6", ChordTemplate::minor_sixth),
+	("M9", ChordTemplate::major_ninth),
+	("-9", ChordTemplate::minor_ninth),
+	("-11", ChordTemplate::minor_eleventh),
+	("M13", ChordTemplate::major_thirteenth),
+	("-13", ChordTemplate::minor_thirteenth),
+	("alt", ChordTemplate::altered_dominant),
+];
+
+/*
+ * The position of a key in the stack of thirds of a chord, given the interval of the key above the root
+ * and the intervals of all keys of the chord (both in half tones within an octave). Some intervals depend
+ * on the other keys: a second is a ninth if there is a third, a fourth is an eleventh if there is a third,
+ * a sixth is a thirteenth if there is a seventh and a minor third next to a major third is a sharp ninth.
+ */
+fn chord_tone_rank(interval: i32, intervals: &[i32]) -> u32 {
+	let has_third = intervals.contains(&3) || intervals.contains(&4);
+	let has_fifth = intervals.contains(&7);
+	let has_seventh = intervals.contains(&10) || intervals.contains(&11);
+	match interval {
+		0 => 0,
+		3 if intervals.contains(&4) => 4,
+		3 | 4 => 1,
+		2 | 5 if !has_third => 1,
+		7 => 2,
+		6 | 8 if !has_fifth => 2,
+		9 if !has_seventh => 3,
+		10 | 11 => 3,
+		1 | 2 => 4,
+		5 | 6 => 5,
+		_ => 6,
+	}
+}
+
+/*
+ * The number of letters a chord tone lies above the root, like 2 for the third, given the intervals like in
+ * chord_tone_rank. An augmented fifth (8 half tones) is a fifth, if the chord has no perfect fifth, and a
+ * diminished seventh (9 half tones) is a seventh above a diminished fifth without other seventh.
+ */
+fn chord_tone_steps(interval: i32, intervals: &[i32]) -> UnspecifiedInterval {
+	let has_fifth = intervals.contains(&7);
+	let has_seventh = intervals.contains(&10) || intervals.contains(&11);
+	let steps = match interval {
+		0 => 0,
+		1 | 2 => 1,
+		3 if intervals.contains(&4) => 1,
+		3 | 4 => 2,
+		5 => 3,
+		6 if has_fifth => 3,
+		6 | 7 => 4,
+		8 if !has_fifth => 4,
+		9 if intervals.contains(&6) && !has_fifth && !has_seventh => 6,
+		8 | 9 => 5,
+		_ => 6,
+	};
+	UnspecifiedInterval::new(steps)
 }
 
 impl KeyChord {
+	/*
+	 * Builds the chord in root position, with the keys ordered by their interval above the keynote.
+	 */
 	pub fn from_chord_template(chord_template: &ChordTemplate, keynote: Key) -> KeyChord {
-		let keys = chord_template.intervals.iter().map(|interval| keynote + *interval).collect();
-		KeyChord { keys }
+		let mut intervals: Vec<Interval> = chord_template.intervals.iter().cloned().collect();
+		intervals.sort_by_key(|interval| interval.halftones);
+		let keys: Vec<Key> = intervals.iter().map(|interval| keynote + *interval).collect();
+		KeyChord::from_keys(keynote, &keys)
+	}
+
+	/*
+	 * Builds a chord from keys in voice order, so the first key is the bass. Repeated keys are dropped.
+	 */
+	pub fn from_keys(root: Key, keys: &[Key]) -> KeyChord {
+		let mut unique_keys: Vec<Key> = Vec::new();
+		for key in keys {
+			if !unique_keys.contains(key) {
+				unique_keys.push(*key);
+			}
+		}
+		KeyChord { root, keys: unique_keys }
+	}
+
+	/*
+	 * The lowest key of the chord. An empty chord has its root as bass.
+	 */
+	pub fn bass(&self) -> Key {
+		self.keys.first().cloned().unwrap_or(self.root)
+	}
+
+	/*
+	 * Returns the chord with its keys ordered like stacked thirds above the root: third, fifth, sixth or
+	 * seventh and then the ninth, eleventh and thirteenth, so C9 is ordered c, e, g, b-flat, d.
+	 */
+	pub fn root_position(&self) -> KeyChord {
+		let halftones: Vec<i32> = self.keys.iter().map(|key| Key::to_interval(self.root, *key).halftones).collect();
+		let mut keys = self.keys.clone();
+		keys.sort_by_key(|key| {
+			let interval = Key::to_interval(self.root, *key).halftones;
+			(chord_tone_rank(interval, &halftones), interval)
+		});
+		KeyChord { root: self.root, keys }
+	}
+
+	/*
+	 * Returns the n-th inversion of the chord: 0 is root position, 1 has the second key of the root
+	 * position in the bass and so on. n wraps around the number of keys.
+	 */
+	pub fn invert(&self, n: usize) -> KeyChord {
+		let mut chord = self.root_position();
+		if !chord.keys.is_empty() {
+			let len = chord.keys.len();
+			chord.keys.rotate_left(n % len);
+		}
+		chord
+	}
+
+	/*
+	 * Returns the number of the inversion this chord is in, so 0 for root position.
+	 */
+	pub fn inversion(&self) -> usize {
+		let bass = self.bass();
+		self.root_position().keys.iter().position(|key| *key == bass).unwrap_or(0)
+	}
+
+	/*
+	 * Returns the chord with the given key in the bass. If the key is a tone of the chord, this is the
+	 * matching inversion, otherwise the key is added below the chord in root position (a slash chord).
+	 */
+	pub fn with_bass(&self, bass: Key) -> KeyChord {
+		let chord = self.root_position();
+		match chord.keys.iter().position(|key| *key == bass) {
+			Some(inversion) => chord.invert(inversion),
+			None => {
+				let mut keys = vec![bass];
+				keys.extend(chord.keys);
+				KeyChord { root: self.root, keys }
+			},
+		}
+	}
+
+	/*
+	 * Spells the keys of this chord relative to the given root, in the order of the keys.
+	 * Every key is spelled with the letter of its chord tone above the root, so the major chord on
+	 * e-flat is spelled e-flat, g, b-flat and the augmented chord on c is spelled c, e, g-sharp.
+	 */
+	pub fn spelled_keys(&self, root: SpelledKey) -> Vec<SpelledKey> {
+		let intervals: Vec<i32> = self.keys.iter().map(|key| Key::to_interval(root.to_key(), *key).halftones).collect();
+		intervals.iter().map(|interval| root.above(Interval { halftones: *interval }, chord_tone_steps(*interval, &intervals))).collect()
 	}
 }
 
 impl PitchChord {
+	/*
+	 * Builds a chord from the given pitches, which are sorted and of which repeated pitches are dropped.
+	 */
+	pub fn from_pitches(pitches: &[Pitch]) -> PitchChord {
+		let mut pitches = pitches.to_vec();
+		pitches.sort();
+		pitches.dedup();
+		PitchChord { pitches }
+	}
+
+	/*
+	 * Stacks the keys of the chord in their order, starting with the bass in the given octave.
+	 * Every key is placed on the next pitch above the previous one.
+	 */
 	pub fn from_chord_and_octave(chord: &KeyChord, octave: Octave) -> PitchChord {
-		let pitches = chord.keys.iter().map(|key| Pitch::from_key_and_octave(*key, octave)).collect();
+		let mut pitches: Vec<Pitch> = Vec::new();
+		for key in chord.keys.iter() {
+			let pitch = match pitches.last() {
+				Some(previous) => (*previous + Interval::minor_second()).get_overlying_pitch(*key),
+				None => Pitch::from_key_and_octave(*key, octave),
+			};
+			pitches.push(pitch);
+		}
 		PitchChord { pitches }
 	}
 
@@ -62,7 +379,38 @@ impl PitchChord {
 			let vec = pitch_range.inner_pitches(key);
 			pitches.extend(&vec);
 		}
-		PitchChord { pitches: pitches.iter().cloned().collect() }
+		PitchChord::from_pitches(&pitches)
+	}
+
+	pub fn bass(&self) -> Option<Pitch> {
+		self.pitches.first().cloned()
+	}
+
+	/*
+	 * Returns the keys of the chord from the lowest pitch upwards, without repetitions.
+	 */
+	pub fn to_key_chord(&self, root: Key) -> KeyChord {
+		let keys: Vec<Key> = self.pitches.iter().map(|pitch| Key::from(*pitch)).collect();
+		KeyChord::from_keys(root, &keys)
+	}
+
+	/*
+	 * Inverts the chord n times by moving the lowest pitch up by octaves until it lies above the highest pitch.
+	 */
+	pub fn invert(&self, n: usize) -> PitchChord {
+		let mut pitches = self.pitches.clone();
+		for _ in 0..n {
+			if pitches.len() < 2 {
+				break;
+			}
+			let mut pitch = pitches.remove(0);
+			let highest = pitches[pitches.len() - 1];
+			while pitch <= highest {
+				pitch += Interval::octave();
+			}
+			pitches.push(pitch);
+		}
+		PitchChord { pitches }
 	}
 }
 
@@ -105,19 +453,125 @@ mod tests {
 		assert_eq!(minor_cis_chord.keys.len(), 3);
 	}
 
+	#[test]
+	pub fn chord_catalogue() {
+		let half_diminished_b_chord = KeyChord::from_chord_template(&ChordTemplate::half_diminished_seventh(), Key::b());
+		let names: Vec<String> = half_diminished_b_chord.spelled_keys(SpelledKey::from_key(Key::b())).iter().map(|key| key.to_string()).collect();
+		assert_eq!(names, vec!["b", "d", "f", "a"]);
+
+		let diminished_seventh_cis_chord = KeyChord::from_chord_template(&ChordTemplate::diminished_seventh(), Key::cis());
+		assert_eq!(diminished_seventh_cis_chord.keys.len(), 4);
+		assert!(diminished_seventh_cis_chord.keys.contains(&Key::ais()));
+
+		let thirteenth_g_chord = KeyChord::from_chord_template(&ChordTemplate::dominant_thirteenth(), Key::g());
+		assert!(thirteenth_g_chord.keys.contains(&Key::e()));
+		assert!(!thirteenth_g_chord.keys.contains(&Key::c()));
+
+		for (_, chord_template) in ChordTemplate::all() {
+			assert!(chord_template.intervals.contains(&Interval::prime()));
+		}
+	}
+
+	#[test]
+	pub fn chord_template_from_symbol() {
+		assert_eq!(ChordTemplate::from_symbol(""), Some(ChordTemplate::major()));
+		assert_eq!(ChordTemplate::from_symbol("m7b5"), Some(ChordTemplate::half_diminished_seventh()));
+		assert_eq!(ChordTemplate::from_symbol("ø"), Some(ChordTemplate::half_diminished_seventh()));
+		assert_eq!(ChordTemplate::from_symbol("-7"), Some(ChordTemplate::minor_seventh()));
+		assert_eq!(ChordTemplate::from_symbol("7#9"), Some(ChordTemplate::dominant_seventh_sharp_ninth()));
+		assert_eq!(ChordTemplate::from_symbol("mMaj7"), Some(ChordTemplate::minor_major_seventh()));
+		assert_eq!(ChordTemplate::from_symbol("7b6b9"), None);
+		for (symbol, chord_template) in ChordTemplate::all() {
+			assert_eq!(ChordTemplate::from_symbol(symbol), Some(chord_template));
+		}
+	}
+
+	#[test]
+	pub fn spelled_chords() {
+		let e_flat = SpelledKey::from_key_with_flats(Key::dis());
+		let major_e_flat_chord = KeyChord::from_chord_template(&ChordTemplate::major(), Key::dis());
+		let names: Vec<String> = major_e_flat_chord.spelled_keys(e_flat).iter().map(|key| key.to_string()).collect();
+		assert_eq!(names, vec!["eb", "g", "bb"]);
+
+		let d_sharp = SpelledKey::from_key_with_sharps(Key::dis());
+		let minor_d_sharp_chord = KeyChord::from_chord_template(&ChordTemplate::minor(), Key::dis());
+		let names: Vec<String> = minor_d_sharp_chord.spelled_keys(d_sharp).iter().map(|key| key.to_string()).collect();
+		assert_eq!(names, vec!["d#", "f#", "a#"]);
+
+		let c = SpelledKey::from_key(Key::c());
+		let spell = |chord_template: ChordTemplate| -> Vec<String> {
+			KeyChord::from_chord_template(&chord_template, Key::c()).spelled_keys(c).iter().map(|key| key.to_string()).collect()
+		};
+		assert_eq!(spell(ChordTemplate::augmented()), vec!["c", "e", "g#"]);
+		assert_eq!(spell(ChordTemplate::diminished_seventh()), vec!["c", "eb", "gb", "bbb"]);
+		assert_eq!(spell(ChordTemplate::major_sixth()), vec!["c", "e", "g", "a"]);
+		assert_eq!(spell(ChordTemplate::dominant_seventh_sharp_ninth()), vec!["c", "e", "g", "bb", "d#"]);
+		assert_eq!(spell(ChordTemplate::suspended_fourth()), vec!["c", "f", "g"]);
+		let names: Vec<String> = "C7b5".parse::<KeyChord>().unwrap().spelled_keys(c).iter().map(|key| key.to_string()).collect();
+		assert_eq!(names, vec!["c", "e", "gb", "bb"]);
+	}
+
 	#[test]
 	pub fn pitch_chord_from_chord_and_pitch_range() {
 		let pitch_range = PitchRange { lower: Pitch { value: 24 }, upper: Pitch { value: 42 }};
 		let chord = KeyChord::from_chord_template(&ChordTemplate::major(), Key::a());
 		let pitch_chord = PitchChord::from_chord_and_pitch_range(chord, pitch_range);
-		let mut asserted_pitches = HashSet::new();
-		asserted_pitches.insert(Pitch { value: 24 });
-		asserted_pitches.insert(Pitch { value: 28 });
-		asserted_pitches.insert(Pitch { value: 31 });
-		asserted_pitches.insert(Pitch { value: 36 });
-		asserted_pitches.insert(Pitch { value: 40 });
+		let asserted_pitches: Vec<Pitch> = [24, 28, 31, 36, 40].iter().map(|value| Pitch { value: *value }).collect();
 
 		assert_eq!(pitch_chord.pitches, asserted_pitches);
 		assert_eq!(pitch_chord.pitches.len(), 5);
 	}
+
+	#[test]
+	pub fn chord_inversions() {
+		let major_c_chord = KeyChord::from_chord_template(&ChordTemplate::dominant_seventh(), Key::c());
+		assert_eq!(major_c_chord.keys, vec![Key::c(), Key::e(), Key::g(), Key::ais()]);
+		assert_eq!(major_c_chord.inversion(), 0);
+
+		let first_inversion = major_c_chord.invert(1);
+		assert_eq!(first_inversion.keys, vec![Key::e(), Key::g(), Key::ais(), Key::c()]);
+		assert_eq!(first_inversion.bass(), Key::e());
+		assert_eq!(first_inversion.root, Key::c());
+		assert_eq!(first_inversion.inversion(), 1);
+		assert_eq!(first_inversion.invert(0), major_c_chord);
+		assert_eq!(major_c_chord.invert(5), first_inversion);
+		assert_eq!(major_c_chord.with_bass(Key::ais()), major_c_chord.invert(3));
+
+		// the ninth is stacked above the seventh
+		let ninth_chord = KeyChord::from_chord_template(&ChordTemplate::dominant_ninth(), Key::c());
+		assert_eq!(ninth_chord.root_position().keys, vec![Key::c(), Key::e(), Key::g(), Key::ais(), Key::d()]);
+		assert_eq!(ninth_chord.invert(1).bass(), Key::e());
+		assert_eq!(ninth_chord.invert(4).bass(), Key::d());
+		assert_eq!(ninth_chord.with_bass(Key::e()).inversion(), 1);
+		assert_eq!(ninth_chord.with_bass(Key::ais()).inversion(), 3);
+		assert_eq!(ninth_chord.invert(2).root_position(), ninth_chord);
+
+		let added_ninth_chord = KeyChord::from_chord_template(&ChordTemplate::added_ninth(), Key::c());
+		assert_eq!(added_ninth_chord.keys, vec![Key::c(), Key::e(), Key::g(), Key::d()]);
+		assert_eq!(added_ninth_chord.invert(1).keys, vec![Key::e(), Key::g(), Key::d(), Key::c()]);
+		assert_eq!(added_ninth_chord.with_bass(Key::g()).inversion(), 2);
+		assert_eq!(added_ninth_chord.with_bass(Key::d()).inversion(), 3);
+
+		let sixth_chord = KeyChord::from_chord_template(&ChordTemplate::major_sixth(), Key::c());
+		assert_eq!(sixth_chord.invert(3).bass(), Key::a());
+		let suspended_chord = KeyChord::from_chord_template(&ChordTemplate::suspended_second(), Key::c());
+		assert_eq!(suspended_chord.invert(1).bass(), Key::d());
+
+		let slash_chord = KeyChord::from_chord_template(&ChordTemplate::major(), Key::c()).with_bass(Key::d());
+		assert_eq!(slash_chord.keys, vec![Key::d(), Key::c(), Key::e(), Key::g()]);
+		assert_eq!(slash_chord.root, Key::c());
+	}
+
+	#[test]
+	pub fn pitch_chord_inversions() {
+		let chord = KeyChord::from_chord_template(&ChordTemplate::major(), Key::c()).invert(1);
+		let pitch_chord = PitchChord::from_chord_and_octave(&chord, Octave { value: 1 });
+		let asserted_pitches: Vec<Pitch> = [19, 22, 27].iter().map(|value| Pitch { value: *value }).collect();
+		assert_eq!(pitch_chord.pitches, asserted_pitches);
+		assert_eq!(pitch_chord.bass(), Some(Pitch { value: 19 }));
+
+		let asserted_pitches: Vec<Pitch> = [27, 31, 34].iter().map(|value| Pitch { value: *value }).collect();
+		assert_eq!(pitch_chord.invert(2).pitches, asserted_pitches);
+		assert_eq!(pitch_chord.invert(2).to_key_chord(Key::c()).inversion(), 0);
+	}
 }
@@ -0,0 +1,268 @@
+use crate::keys::Key;
+use crate::intervals::UnspecifiedInterval;
+use crate::scale::KeyScale;
+use crate::key_signature::KeySignature;
+use super::KeyChord;
+use super::diatonic::{ChordQuality, DiatonicChord};
+use super::generate::HarmonicFunction;
+use super::roman_numeral::RomanNumeral;
+
+/*
+ * The ways to get from one key to another:
+ * PivotChord uses a chord, which belongs to both keys, CommonTone moves to a chord of the new key, which is not
+ * part of the old key but keeps a key of the old tonic, Chromatic moves from a chord of the old key to a chord of
+ * the new key with half tone steps and Direct simply starts with the new tonic.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ModulationType {
+	PivotChord,
+	CommonTone,
+	Chromatic,
+	Direct,
+}
+
+/*
+ * A modulation as sequence of chords, starting with the tonic of the old key and ending with the dominant
+ * seventh chord and the tonic of the new key. Modulations with a lower score sound smoother.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Modulation {
+	pub modulation_type: ModulationType,
+	pub chords: Vec<KeyChord>,
+	pub score: u32,
+}
+
+impl ModulationType {
+	/*
+	 * The more abrupt types of modulations get a higher score.
+	 */
+	fn cost(self) -> u32 {
+		match self {
+			ModulationType::PivotChord => 0,
+			ModulationType::CommonTone => 2,
+			ModulationType::Chromatic => 3,
+			ModulationType::Direct => 6,
+		}
+	}
+}
+
+/*
+ * Returns the distance of the keys on the circle of fifths. Scales without key signature are compared by
+ * their keynotes.
+ */
+fn fifths_distance(source: &KeyScale, target: &KeyScale) -> u32 {
+	match (KeySignature::from_key_scale(source), KeySignature::from_key_scale(target)) {
+		(Some(source), Some(target)) => source.distance(target) as u32,
+		_ => {
+			let fifths = (Key::to_interval(source.keynote(), target.keynote()).halftones * 7).rem_euclid(12);
+			fifths.min(12 - fifths) as u32
+		},
+	}
+}
+
+/*
+ * The half tone distance between two keys, at most 6.
+ */
+fn key_distance(key: Key, other_key: Key) -> u32 {
+	let halftones = Key::to_interval(key, other_key).halftones;
+	halftones.min(12 - halftones) as u32
+}
+
+/*
+ * How far the keys of the second chord are from the keys of the first chord: the sum of the distances of every
+ * key of the second chord to the nearest key of the first chord.
+ */
+fn chord_distance(chord: &KeyChord, next_chord: &KeyChord) -> u32 {
+	next_chord.keys.iter()
+		.map(|next_key| chord.keys.iter().map(|key| key_distance(*key, *next_key)).min().unwrap_or(0))
+		.sum()
+}
+
+fn has_same_keys(chord: &KeyChord, other_chord: &KeyChord) -> bool {
+	chord.keys.len() == other_chord.keys.len() && chord.keys.iter().all(|key| other_chord.keys.contains(key))
+}
+
+fn modulation(modulation_type: ModulationType, chords: Vec<KeyChord>, base_score: u32) -> Modulation {
+	let smoothness: u32 = chords.windows(2).map(|pair| chord_distance(&pair[0], &pair[1])).sum();
+	Modulation { modulation_type, score: base_score + modulation_type.cost() + smoothness, chords }
+}
+
+/*
+ * Suggests modulations from the source key to the target key, ordered from the best to the worst score.
+ * The score adds the distance of the keys on the circle of fifths, the smoothness of the chord sequence
+ * (how far the keys move from chord to chord) and the abruptness of the type of modulation. Pivot chords,
+ * which are predominant chords in the target key, are preferred.
+ * Both keys have to be heptatonic scales, as the chords are built from their degrees. Returns no
+ * modulations for other scales and if both keys are the same.
+ */
+pub fn plan_modulations(source: &KeyScale, target: &KeyScale) -> Vec<Modulation> {
+	let num_degrees = UnspecifiedInterval::octave().value as usize;
+	if source.keys.len() != num_degrees || target.keys.len() != num_degrees || source == target {
+		return Vec::new();
+	}
+
+	let source_tonic = source.triad(0).chord;
+	let target_tonic = target.triad(0).chord;
+	let target_dominant = RomanNumeral::new(4, 0, ChordQuality::DominantSeventh, 0).to_key_chord(target).unwrap();
+	let base_score = 2 * fifths_distance(source, target);
+	let sequence = |chords: &[&KeyChord]| -> Vec<KeyChord> {
+		let mut sequence = vec![source_tonic.clone()];
+		sequence.extend(chords.iter().map(|chord| (*chord).clone()));
+		sequence.push(target_dominant.clone());
+		sequence.push(target_tonic.clone());
+		sequence
+	};
+	let is_usable = |diatonic_chord: &DiatonicChord| diatonic_chord.quality == ChordQuality::Major || diatonic_chord.quality == ChordQuality::Minor;
+	let source_triads = source.triads();
+	let target_triads = target.triads();
+
+	let mut modulations = Vec::new();
+	for source_chord in source_triads.iter().filter(|chord| is_usable(chord) && chord.degree != 0) {
+		for target_chord in target_triads.iter().filter(|chord| is_usable(chord) && chord.degree != 4) {
+			if has_same_keys(&source_chord.chord, &target_chord.chord) {
+				let function_cost = if HarmonicFunction::from_degree(target_chord.degree) == HarmonicFunction::Predominant { 0 } else { 1 };
+				modulations.push(modulation(ModulationType::PivotChord, sequence(&[&source_chord.chord]), base_score + function_cost));
+			}
+		}
+	}
+
+	// the transition chords of common tone and chromatic modulations are the major and minor triads of the new key
+	// apart from its tonic and dominant, which follow anyway
+	let is_transition_chord = |chord: &&DiatonicChord| is_usable(chord) && chord.degree != 0 && chord.degree != 4;
+	let is_foreign = |chord: &DiatonicChord| chord.chord.keys.iter().any(|key| source.degree_of(*key).is_none());
+
+	// the common tone is held, while the other keys move to the chord, which does not belong to the old key
+	let common_tone_chord = target_triads.iter()
+		.filter(is_transition_chord)
+		.filter(|chord| is_foreign(chord) && chord.chord.keys.iter().any(|key| source_tonic.keys.contains(key)))
+		.min_by_key(|chord| chord_distance(&source_tonic, &chord.chord));
+	if let Some(common_tone_chord) = common_tone_chord {
+		modulations.push(modulation(ModulationType::CommonTone, sequence(&[&common_tone_chord.chord]), base_score));
+	}
+
+	// a chromatic step moves at least one key by a half tone and no key further
+	let mut chromatic_steps: Vec<(u32, &DiatonicChord, &DiatonicChord)> = Vec::new();
+	for source_chord in source_triads.iter().filter(|chord| is_usable(chord)) {
+		for target_chord in target_triads.iter().filter(is_transition_chord) {
+			let is_chromatic = is_foreign(target_chord)
+				&& target_chord.chord.keys.iter().all(|key| source_chord.chord.keys.iter().any(|source_key| key_distance(*source_key, *key) <= 1));
+			if is_chromatic {
+				chromatic_steps.push((chord_distance(&source_chord.chord, &target_chord.chord), source_chord, target_chord));
+			}
+		}
+	}
+	if let Some((_, source_chord, target_chord)) = chromatic_steps.iter().min_by_key(|(distance, _, _)| *distance) {
+		let chords = if source_chord.degree == 0 { sequence(&[&target_chord.chord]) } else { sequence(&[&source_chord.chord, &target_chord.chord]) };
+		// a chromatic step from the tonic may already be suggested as common tone modulation
+		if !modulations.iter().any(|modulation| modulation.chords == chords) {
+			modulations.push(modulation(ModulationType::Chromatic, chords, base_score));
+		}
+	}
+
+	let mut direct_chords = sequence(&[]);
+	direct_chords.insert(1, target_tonic.clone());
+	modulations.push(modulation(ModulationType::Direct, direct_chords, base_score));
+
+	modulations.sort_by_key(|modulation| modulation.score);
+	modulations
+}
+
+/*
+ * Returns the best modulation from the source key to the target key, see plan_modulations.
+ */
+pub fn best_modulation(source: &KeyScale, target: &KeyScale) -> Option<Modulation> {
+	plan_modulations(source, target).into_iter().next()
+}
+
+impl Modulation {
+	/*
+	 * The number of half tones the keys move from chord to chord, see plan_modulations.
+	 */
+	pub fn smoothness(&self) -> u32 {
+		self.chords.windows(2).map(|pair| chord_distance(&pair[0], &pair[1])).sum()
+	}
+
+	/*
+	 * The chords, which are neither the tonic of the old key nor the final cadence in the new key.
+	 */
+	pub fn transition_chords(&self) -> &[KeyChord] {
+		&self.chords[1..self.chords.len() - 2]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::scale::ScaleTemplate;
+	use crate::chords::ChordTemplate;
+
+	fn major(key: Key) -> KeyScale {
+		KeyScale::from_scale_template(&ScaleTemplate::major(), key)
+	}
+
+	#[test]
+	pub fn pivot_chord_modulation() {
+		let modulations = plan_modulations(&major(Key::c()), &major(Key::g()));
+		let best = &modulations[0];
+		assert_eq!(best.modulation_type, ModulationType::PivotChord);
+		// a minor is vi in c major and ii in g major
+		assert_eq!(best.transition_chords(), &[KeyChord::from_chord_template(&ChordTemplate::minor(), Key::a())][..]);
+		assert_eq!(best.chords[best.chords.len() - 2].keys, vec![Key::d(), Key::fis(), Key::a(), Key::c()]);
+		assert_eq!(best.chords[best.chords.len() - 1].keys, vec![Key::g(), Key::b(), Key::d()]);
+
+		for pair in modulations.windows(2) {
+			assert!(pair[0].score <= pair[1].score);
+		}
+		assert!(modulations.iter().any(|modulation| modulation.modulation_type == ModulationType::Direct));
+	}
+
+	#[test]
+	pub fn distant_modulations() {
+		let modulations = plan_modulations(&major(Key::c()), &major(Key::gis()));
+		assert!(modulations.iter().all(|modulation| modulation.modulation_type != ModulationType::PivotChord));
+		// c minor keeps c and g of c major and belongs to a-flat major
+		let common_tone = modulations.iter().find(|modulation| modulation.modulation_type == ModulationType::CommonTone).unwrap();
+		assert_eq!(common_tone.transition_chords(), &[KeyChord::from_chord_template(&ChordTemplate::minor(), Key::c())][..]);
+		// all chords of g major, which keep a key of c major, belong to c major as well
+		let modulations = plan_modulations(&major(Key::c()), &major(Key::g()));
+		assert!(modulations.iter().all(|modulation| modulation.modulation_type != ModulationType::CommonTone));
+
+		let close = best_modulation(&major(Key::c()), &major(Key::g())).unwrap();
+		let distant = best_modulation(&major(Key::c()), &major(Key::fis())).unwrap();
+		assert!(close.score < distant.score);
+
+		let a_minor = KeyScale::from_scale_template(&ScaleTemplate::minor(), Key::a());
+		let modulation = best_modulation(&a_minor, &major(Key::f())).unwrap();
+		assert_eq!(modulation.chords[0].keys, vec![Key::a(), Key::c(), Key::e()]);
+	}
+
+	#[test]
+	pub fn chromatic_modulations() {
+		// a minor moves to a major by raising c to c-sharp, which leads to e major
+		let modulations = plan_modulations(&major(Key::c()), &major(Key::e()));
+		let chromatic = modulations.iter().find(|modulation| modulation.modulation_type == ModulationType::Chromatic).unwrap();
+		assert_eq!(chromatic.transition_chords(), &[
+			KeyChord::from_chord_template(&ChordTemplate::minor(), Key::a()),
+			KeyChord::from_chord_template(&ChordTemplate::major(), Key::a()),
+		][..]);
+
+		// the chromatic step from c major to c minor is the common tone modulation to a-flat major
+		let modulations = plan_modulations(&major(Key::c()), &major(Key::gis()));
+		for (index, modulation) in modulations.iter().enumerate() {
+			assert!(modulations[index + 1..].iter().all(|other| other.chords != modulation.chords));
+		}
+	}
+
+	#[test]
+	pub fn modulations_without_key_change() {
+		assert!(plan_modulations(&major(Key::c()), &major(Key::c())).is_empty());
+		assert_eq!(best_modulation(&major(Key::c()), &major(Key::c())), None);
+
+		// only heptatonic scales have the degrees to build the chords from
+		let pentatonic = KeyScale::from_scale_template(&ScaleTemplate::major_pentatonic(), Key::c());
+		assert!(plan_modulations(&major(Key::c()), &pentatonic).is_empty());
+		assert!(plan_modulations(&pentatonic, &major(Key::g())).is_empty());
+		let keynote_scale = KeyScale::from_scale_template(&ScaleTemplate::new(), Key::c());
+		assert_eq!(best_modulation(&keynote_scale, &major(Key::g())), None);
+	}
+}
@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::fmt;
+
+use crate::keys::Key;
+use crate::intervals::Interval;
+use crate::parse::ParseError;
+use super::{ChordTemplate, KeyChord};
+use super::diatonic::ChordQuality;
+
+/*
+ * The neo-Riemannian transformations, which exchange a major triad with a minor triad by moving a single key:
+ * Parallel keeps the root and fifth (c major to c minor), Relative keeps the root and third of the major triad
+ * (c major to a minor) and LeadingToneExchange keeps the third and fifth of the major triad (c major to e minor).
+ * Every transformation is its own inverse.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Transformation {
+	Parallel,
+	LeadingToneExchange,
+	Relative,
+}
+
+/*
+ * A sequence of transformations, which are applied from left to right, so "PL" first applies P and then L.
+ */
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TransformationChain {
+	pub transformations: Vec<Transformation>,
+}
+
+/*
+ * The position of a triad on the Tonnetz: the position of its root, measured in fifths and major thirds above
+ * c, and whether the triad is major (a triangle pointing up) or minor (pointing down). As twelve fifths and three
+ * major thirds lead back to the same key, fifths lies in 0..4 and major_thirds in 0..3.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TonnetzCoordinates {
+	pub fifths: i32,
+	pub major_thirds: i32,
+	pub is_major: bool,
+}
+
+const TRANSFORMATIONS: [Transformation; 3] = [Transformation::Parallel, Transformation::LeadingToneExchange, Transformation::Relative];
+
+/*
+ * Returns whether the chord is a major triad, or None if it is neither a major nor a minor triad.
+ */
+fn is_major_triad(chord: &KeyChord) -> Option<bool> {
+	match ChordQuality::from_key_chord(&chord.root_position()) {
+		ChordQuality::Major => Some(true),
+		ChordQuality::Minor => Some(false),
+		_ => None,
+	}
+}
+
+/*
+ * Returns the major or minor triad in root position, or None for other chords.
+ */
+fn triad(chord: &KeyChord) -> Option<KeyChord> {
+	let chord_template = if is_major_triad(chord)? { ChordTemplate::major() } else { ChordTemplate::minor() };
+	Some(KeyChord::from_chord_template(&chord_template, chord.root))
+}
+
+impl Transformation {
+	/*
+	 * Applies the transformation to a major or minor triad and returns the new triad in root position, or None
+	 * for other chords.
+	 */
+	pub fn apply(self, chord: &KeyChord) -> Option<KeyChord> {
+		let is_major = is_major_triad(chord)?;
+		let root = match (self, is_major) {
+			(Transformation::Parallel, _) => chord.root,
+			(Transformation::Relative, true) => chord.root - Interval::minor_third(),
+			(Transformation::Relative, false) => chord.root + Interval::minor_third(),
+			(Transformation::LeadingToneExchange, true) => chord.root + Interval::major_third(),
+			(Transformation::LeadingToneExchange, false) => chord.root - Interval::major_third(),
+		};
+		let chord_template = if is_major { ChordTemplate::minor() } else { ChordTemplate::major() };
+		Some(KeyChord::from_chord_template(&chord_template, root))
+	}
+}
+
+impl TransformationChain {
+	pub fn new(transformations: &[Transformation]) -> TransformationChain {
+		TransformationChain { transformations: transformations.to_vec() }
+	}
+
+	/*
+	 * Applies all transformations one after another. Returns None if the chord is neither a major nor a
+	 * minor triad.
+	 */
+	pub fn apply(&self, chord: &KeyChord) -> Option<KeyChord> {
+		self.transformations.iter().try_fold(triad(chord)?, |chord, transformation| transformation.apply(&chord))
+	}
+
+	/*
+	 * Returns the triads reached after every transformation, starting with the given triad.
+	 */
+	pub fn progression(&self, chord: &KeyChord) -> Option<Vec<KeyChord>> {
+		let mut chords = vec![triad(chord)?];
+		for transformation in self.transformations.iter() {
+			let next_chord = transformation.apply(&chords[chords.len() - 1])?;
+			chords.push(next_chord);
+		}
+		Some(chords)
+	}
+
+	/*
+	 * Finds one of the shortest chains, which transforms the first triad into the second one. Returns None if
+	 * one of the chords is neither a major nor a minor triad.
+	 */
+	pub fn shortest_path(from: &KeyChord, to: &KeyChord) -> Option<TransformationChain> {
+		let start = triad(from)?;
+		let target = triad(to)?;
+		let mut visited = vec![start.clone()];
+		let mut queue = VecDeque::new();
+		queue.push_back((start, Vec::new()));
+		while let Some((chord, transformations)) = queue.pop_front() {
+			if chord == target {
+				return Some(TransformationChain { transformations });
+			}
+			for transformation in TRANSFORMATIONS.iter() {
+				let next_chord = transformation.apply(&chord)?;
+				if !visited.contains(&next_chord) {
+					visited.push(next_chord.clone());
+					let mut next_transformations = transformations.clone();
+					next_transformations.push(*transformation);
+					queue.push_back((next_chord, next_transformations));
+				}
+			}
+		}
+		None
+	}
+}
+
+impl TonnetzCoordinates {
+	pub fn from_key_chord(chord: &KeyChord) -> Option<TonnetzCoordinates> {
+		let is_major = is_major_triad(chord)?;
+		let halftones = Key::to_interval(Key::c(), chord.root).halftones;
+		for fifths in 0..4 {
+			for major_thirds in 0..3 {
+				if (fifths * 7 + major_thirds * 4) % 12 == halftones {
+					return Some(TonnetzCoordinates { fifths, major_thirds, is_major });
+				}
+			}
+		}
+		None
+	}
+}
+
+impl fmt::Display for Transformation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Transformation::Parallel => write!(f, "P"),
+			Transformation::LeadingToneExchange => write!(f, "L"),
+			Transformation::Relative => write!(f, "R"),
+		}
+	}
+}
+
+impl fmt::Display for TransformationChain {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for transformation in self.transformations.iter() {
+			transformation.fmt(f)?;
+		}
+		Ok(())
+	}
+}
+
+impl FromStr for TransformationChain {
+	type Err = ParseError;
+
+	/*
+	 * Parses chains like "PLR" or "p l r", ignoring whitespace.
+	 */
+	fn from_str(s: &str) -> Result<TransformationChain, ParseError> {
+		let s = s.trim();
+		if s.is_empty() {
+			return Err(ParseError::Empty);
+		}
+		let transformations = s.chars().filter(|c| !c.is_whitespace()).map(|c| match c.to_ascii_uppercase() {
+			'P' => Ok(Transformation::Parallel),
+			'L' => Ok(Transformation::LeadingToneExchange),
+			'R' => Ok(Transformation::Relative),
+			_ => Err(ParseError::InvalidTransformation(s.to_string())),
+		}).collect::<Result<Vec<Transformation>, ParseError>>()?;
+		Ok(TransformationChain { transformations })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn major(key: Key) -> KeyChord {
+		KeyChord::from_chord_template(&ChordTemplate::major(), key)
+	}
+
+	fn minor(key: Key) -> KeyChord {
+		KeyChord::from_chord_template(&ChordTemplate::minor(), key)
+	}
+
+	#[test]
+	pub fn single_transformations() {
+		assert_eq!(Transformation::Parallel.apply(&major(Key::c())), Some(minor(Key::c())));
+		assert_eq!(Transformation::Relative.apply(&major(Key::c())), Some(minor(Key::a())));
+		assert_eq!(Transformation::LeadingToneExchange.apply(&major(Key::c())), Some(minor(Key::e())));
+		assert_eq!(Transformation::Relative.apply(&minor(Key::a())), Some(major(Key::c())));
+		assert_eq!(Transformation::LeadingToneExchange.apply(&minor(Key::e())), Some(major(Key::c())));
+		// inverted triads work as well
+		assert_eq!(Transformation::Parallel.apply(&major(Key::c()).invert(1)), Some(minor(Key::c())));
+
+		let dominant = KeyChord::from_chord_template(&ChordTemplate::dominant_seventh(), Key::g());
+		assert_eq!(Transformation::Parallel.apply(&dominant), None);
+	}
+
+	#[test]
+	pub fn transformation_chains() {
+		let chain: TransformationChain = "PLR".parse().unwrap();
+		assert_eq!(chain.to_string(), "PLR");
+		// c major, c minor, a-flat major, f minor
+		assert_eq!(chain.progression(&major(Key::c())).unwrap(), vec![major(Key::c()), minor(Key::c()), major(Key::gis()), minor(Key::f())]);
+		assert_eq!(chain.apply(&major(Key::c())), Some(minor(Key::f())));
+		assert_eq!("p l".parse::<TransformationChain>().unwrap().transformations, vec![Transformation::Parallel, Transformation::LeadingToneExchange]);
+		assert_eq!("PX".parse::<TransformationChain>(), Err(ParseError::InvalidTransformation("PX".to_string())));
+	}
+
+	#[test]
+	pub fn shortest_paths() {
+		let path = TransformationChain::shortest_path(&major(Key::c()), &minor(Key::e())).unwrap();
+		assert_eq!(path.to_string(), "L");
+		let path = TransformationChain::shortest_path(&major(Key::c()), &major(Key::gis())).unwrap();
+		assert_eq!(path.transformations.len(), 2);
+		assert_eq!(path.apply(&major(Key::c())), Some(major(Key::gis())));
+		assert_eq!(TransformationChain::shortest_path(&major(Key::c()), &major(Key::c())).unwrap().transformations, vec![]);
+
+		for key in 0..12 {
+			let path = TransformationChain::shortest_path(&major(Key::c()), &minor(Key::new(key))).unwrap();
+			assert_eq!(path.apply(&major(Key::c())), Some(minor(Key::new(key))));
+		}
+	}
+
+	#[test]
+	pub fn tonnetz_coordinates() {
+		assert_eq!(TonnetzCoordinates::from_key_chord(&major(Key::c())), Some(TonnetzCoordinates { fifths: 0, major_thirds: 0, is_major: true }));
+		assert_eq!(TonnetzCoordinates::from_key_chord(&minor(Key::g())), Some(TonnetzCoordinates { fifths: 1, major_thirds: 0, is_major: false }));
+		assert_eq!(TonnetzCoordinates::from_key_chord(&major(Key::e())), Some(TonnetzCoordinates { fifths: 0, major_thirds: 1, is_major: true }));
+		assert_eq!(TonnetzCoordinates::from_key_chord(&KeyChord::from_chord_template(&ChordTemplate::diminished(), Key::b())), None);
+	}
+}
@@ -0,0 +1,471 @@
+use std::str::FromStr;
+use std::fmt;
+
+use crate::keys::Key;
+use crate::intervals::Interval;
+use crate::scale::{KeyScale, ScaleTemplate};
+use crate::parse::ParseError;
+use super::KeyChord;
+use super::diatonic::ChordQuality;
+
+/*
+ * Chords, which have their own names instead of a degree: the Neapolitan sixth and the augmented sixth chords.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChromaticChord {
+	Neapolitan,
+	ItalianSixth,
+	FrenchSixth,
+	GermanSixth,
+}
+
+/*
+ * A chord relative to a key, like "ii7", "V65/V", "bVI", "N6" or "Ger65".
+ * degree is the scale degree of the root (0 for I), alteration moves the root by half tones (-1 for bVI).
+ * The chord is built on the root with the given quality and inversion, so the quality does not have to be
+ * diatonic (like iv in a major key).
+ * If there is a target, the chord is built in the key of the target (a secondary dominant like V/V), which is
+ * major if the target chord has a major third and minor otherwise.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct RomanNumeral {
+	pub degree: i32,
+	pub alteration: i8,
+	pub quality: ChordQuality,
+	pub inversion: usize,
+	pub target: Option<Box<RomanNumeral>>,
+	pub chromatic_chord: Option<ChromaticChord>,
+}
+
+const NUMERALS: [&str; 7] = ["I", "II", "III", "IV", "V", "VI", "VII"];
+const SEVENTH_CHORD_FIGURES: [&str; 4] = ["7", "65", "43", "42"];
+const TRIAD_FIGURES: [&str; 3] = ["", "6", "64"];
+
+fn has_major_third(quality: ChordQuality) -> bool {
+	matches!(quality, ChordQuality::Major | ChordQuality::Augmented | ChordQuality::MajorSeventh | ChordQuality::DominantSeventh
+		| ChordQuality::AugmentedSeventh | ChordQuality::AugmentedMajorSeventh)
+}
+
+fn is_dominant(quality: ChordQuality) -> bool {
+	matches!(quality, ChordQuality::Major | ChordQuality::DominantSeventh)
+}
+
+fn is_leading_tone_chord(quality: ChordQuality) -> bool {
+	matches!(quality, ChordQuality::Diminished | ChordQuality::DiminishedSeventh | ChordQuality::HalfDiminishedSeventh)
+}
+
+impl ChromaticChord {
+	/*
+	 * The intervals of the augmented sixth chords above the keynote, from the bass (the minor sixth) upwards.
+	 */
+	fn augmented_sixth_intervals(self) -> Vec<Interval> {
+		let sixth = Interval::minor_sixth();
+		match self {
+			ChromaticChord::Neapolitan => vec![],
+			ChromaticChord::ItalianSixth => vec![sixth, Interval::prime(), Interval::tritone()],
+			ChromaticChord::FrenchSixth => vec![sixth, Interval::prime(), Interval::major_second(), Interval::tritone()],
+			ChromaticChord::GermanSixth => vec![sixth, Interval::prime(), Interval::minor_third(), Interval::tritone()],
+		}
+	}
+}
+
+impl RomanNumeral {
+	pub fn new(degree: i32, alteration: i8, quality: ChordQuality, inversion: usize) -> RomanNumeral {
+		RomanNumeral { degree, alteration, quality, inversion, target: None, chromatic_chord: None }
+	}
+
+	/*
+	 * The major chord on the lowered second degree in the given inversion, usually 1 (N6).
+	 */
+	pub fn neapolitan(inversion: usize) -> RomanNumeral {
+		RomanNumeral { chromatic_chord: Some(ChromaticChord::Neapolitan), ..RomanNumeral::new(1, -1, ChordQuality::Major, inversion) }
+	}
+
+	/*
+	 * The italian, french or german augmented sixth chord with the minor sixth of the key in the bass.
+	 */
+	pub fn augmented_sixth(chromatic_chord: ChromaticChord) -> RomanNumeral {
+		if chromatic_chord == ChromaticChord::Neapolitan {
+			return RomanNumeral::neapolitan(1);
+		}
+		let quality = if chromatic_chord == ChromaticChord::GermanSixth { ChordQuality::DominantSeventh } else { ChordQuality::Other };
+		RomanNumeral { chromatic_chord: Some(chromatic_chord), ..RomanNumeral::new(5, -1, quality, 0) }
+	}
+
+	/*
+	 * Returns this chord as secondary chord of the given target, like V7 of V.
+	 */
+	pub fn of(self, target: RomanNumeral) -> RomanNumeral {
+		RomanNumeral { target: Some(Box::new(target)), ..self }
+	}
+
+	/*
+	 * The scale the chord is built in: the key itself or the key of the target.
+	 */
+	fn tonicized_scale(&self, key: &KeyScale) -> KeyScale {
+		match &self.target {
+			Some(target) => {
+				let scale_template = if has_major_third(target.quality) { ScaleTemplate::major() } else { ScaleTemplate::minor() };
+				KeyScale::from_scale_template(&scale_template, target.root(key))
+			},
+			None => key.clone(),
+		}
+	}
+
+	/*
+	 * Returns the root of the chord in the given key. Diminished chords on the seventh degree are built on
+	 * the leading tone, also in keys with a minor seventh degree (so vii°7 in a minor is built on g-sharp).
+	 */
+	pub fn root(&self, key: &KeyScale) -> Key {
+		match self.chromatic_chord {
+			Some(ChromaticChord::Neapolitan) | None => {
+				let scale = self.tonicized_scale(key);
+				if self.degree == 6 && self.alteration == 0 && is_leading_tone_chord(self.quality) {
+					scale.keynote() - Interval::minor_second()
+				} else {
+					scale.key_at(self.degree) + Interval { halftones: self.alteration as i32 }
+				}
+			},
+			Some(_) => key.keynote() + Interval::minor_sixth(),
+		}
+	}
+
+	/*
+	 * Returns the keys of the chord in the given key, or None if the quality is ChordQuality::Other.
+	 */
+	pub fn to_key_chord(&self, key: &KeyScale) -> Option<KeyChord> {
+		match self.chromatic_chord {
+			Some(ChromaticChord::Neapolitan) | None => {
+				let chord_template = self.quality.to_chord_template()?;
+				Some(KeyChord::from_chord_template(&chord_template, self.root(key)).invert(self.inversion))
+			},
+			Some(chromatic_chord) => {
+				let keys: Vec<Key> = chromatic_chord.augmented_sixth_intervals().iter().map(|interval| key.keynote() + *interval).collect();
+				Some(KeyChord::from_keys(self.root(key), &keys))
+			},
+		}
+	}
+
+	/*
+	 * Labels the chord relative to the given heptatonic key. Chords are preferably labelled as diatonic chords,
+	 * then as Neapolitan, augmented sixth or secondary dominant and leading tone chords and at last as chords
+	 * on a (possibly altered) degree of the key. A german sixth chord is never labelled as dominant seventh chord
+	 * on the lowered sixth degree.
+	 * Returns None for keys with more or less than seven keys and for chords, which are no triads or seventh chords.
+	 */
+	pub fn analyze(chord: &KeyChord, key: &KeyScale) -> Option<RomanNumeral> {
+		if key.keys.len() != NUMERALS.len() {
+			return None;
+		}
+		let keynote = key.keynote();
+		for chromatic_chord in [ChromaticChord::GermanSixth, ChromaticChord::FrenchSixth, ChromaticChord::ItalianSixth].iter() {
+			let intervals = chromatic_chord.augmented_sixth_intervals();
+			let is_augmented_sixth = chord.bass() == keynote + Interval::minor_sixth()
+				&& chord.keys.len() == intervals.len()
+				&& intervals.iter().all(|interval| chord.keys.contains(&(keynote + *interval)));
+			if is_augmented_sixth {
+				return Some(RomanNumeral::augmented_sixth(*chromatic_chord));
+			}
+		}
+
+		let quality = ChordQuality::from_key_chord(chord);
+		if quality == ChordQuality::Other {
+			return None;
+		}
+		let inversion = chord.inversion();
+		let root = chord.root;
+
+		let is_diatonic = chord.keys.iter().all(|key_of_chord| key.degree_of(*key_of_chord).is_some());
+		if let (Some(degree), true) = (key.degree_of(root), is_diatonic) {
+			return Some(RomanNumeral::new(degree as i32, 0, quality, inversion));
+		}
+
+		if root == keynote + Interval::minor_second() && quality == ChordQuality::Major {
+			return Some(RomanNumeral::neapolitan(inversion));
+		}
+
+		let secondary = if is_dominant(quality) {
+			Some((4, root + Interval::fourth()))
+		} else if is_leading_tone_chord(quality) {
+			Some((6, root + Interval::minor_second()))
+		} else {
+			None
+		};
+		if let Some((degree, target_root)) = secondary {
+			if let Some(target_degree) = key.degree_of(target_root) {
+				let target_quality = key.triad(target_degree as i32).quality;
+				let numeral = RomanNumeral::new(degree, 0, quality, inversion);
+				if target_degree == 0 {
+					return Some(numeral);
+				}
+				if target_quality == ChordQuality::Major || target_quality == ChordQuality::Minor {
+					return Some(numeral.of(RomanNumeral::new(target_degree as i32, 0, target_quality, 0)));
+				}
+			}
+		}
+
+		for alteration in [0, -1, 1].iter() {
+			if let Some(degree) = key.degree_of(root - Interval { halftones: *alteration as i32 }) {
+				return Some(RomanNumeral::new(degree as i32, *alteration, quality, inversion));
+			}
+		}
+		None
+	}
+}
+
+/*
+ * Realizes a progression of roman numerals separated by whitespace, like "ii7 V7/V V I", in the given key.
+ */
+pub fn realize_progression(progression: &str, key: &KeyScale) -> Result<Vec<KeyChord>, ParseError> {
+	progression.split_whitespace().map(|s| {
+		let roman_numeral: RomanNumeral = s.parse()?;
+		roman_numeral.to_key_chord(key).ok_or_else(|| ParseError::InvalidRomanNumeral(s.to_string()))
+	}).collect()
+}
+
+/*
+ * Labels every chord of the progression with a roman numeral relative to the given key, see RomanNumeral::analyze.
+ */
+pub fn analyze_progression(chords: &[KeyChord], key: &KeyScale) -> Vec<Option<RomanNumeral>> {
+	chords.iter().map(|chord| RomanNumeral::analyze(chord, key)).collect()
+}
+
+/*
+ * Parses a single roman numeral without target, like "bVII", "viiø7", "IM7" or "N6".
+ */
+fn parse_roman_numeral(s: &str) -> Option<RomanNumeral> {
+	let chromatic_chords: &[(&str, ChromaticChord)] = &[
+		("It6", ChromaticChord::ItalianSixth),
+		("It", ChromaticChord::ItalianSixth),
+		("Fr43", ChromaticChord::FrenchSixth),
+		("Fr", ChromaticChord::FrenchSixth),
+		("Ger65", ChromaticChord::GermanSixth),
+		("Ger", ChromaticChord::GermanSixth),
+	];
+	if let Some((_, chromatic_chord)) = chromatic_chords.iter().find(|(name, _)| *name == s) {
+		return Some(RomanNumeral::augmented_sixth(*chromatic_chord));
+	}
+	if let Some(figure) = s.strip_prefix('N') {
+		return TRIAD_FIGURES.iter().position(|triad_figure| *triad_figure == figure).map(RomanNumeral::neapolitan);
+	}
+
+	let mut rest = s;
+	let mut alteration: i8 = 0;
+	loop {
+		if let Some(stripped) = rest.strip_prefix('b') {
+			alteration -= 1;
+			rest = stripped;
+		} else if let Some(stripped) = rest.strip_prefix('#') {
+			alteration += 1;
+			rest = stripped;
+		} else {
+			break;
+		}
+		// at most a double flat or double sharp
+		if alteration.abs() > 2 {
+			return None;
+		}
+	}
+
+	let numeral_length = rest.find(|c: char| !"IViv".contains(c)).unwrap_or(rest.len());
+	let numeral = &rest[..numeral_length];
+	let is_upper_case = numeral.chars().all(|c| c.is_ascii_uppercase());
+	if !is_upper_case && !numeral.chars().all(|c| c.is_ascii_lowercase()) {
+		return None;
+	}
+	let degree = NUMERALS.iter().position(|candidate| *candidate == numeral.to_ascii_uppercase())? as i32;
+	rest = &rest[numeral_length..];
+
+	let mark = ["°", "o", "ø", "+"].iter().find(|mark| rest.starts_with(**mark)).cloned();
+	if let Some(mark) = mark {
+		rest = &rest[mark.len()..];
+	}
+	let major_seventh = ["maj", "M"].iter().find(|prefix| rest.starts_with(**prefix)).cloned();
+	if let Some(prefix) = major_seventh {
+		rest = &rest[prefix.len()..];
+	}
+	let major_seventh = major_seventh.is_some();
+	// a half-diminished chord is always a seventh chord, so "viiø" is the same as "viiø7"
+	let figure = if rest.is_empty() && (major_seventh || mark == Some("ø")) { "7" } else { rest };
+	let figure = if figure == "2" { "42" } else { figure };
+
+	let mark = mark.map(|mark| if mark == "o" { "°" } else { mark });
+	let (quality, inversion) = if let Some(inversion) = SEVENTH_CHORD_FIGURES.iter().position(|seventh_figure| *seventh_figure == figure) {
+		let quality = match (is_upper_case, mark, major_seventh) {
+			(true, None, false) => ChordQuality::DominantSeventh,
+			(true, None, true) => ChordQuality::MajorSeventh,
+			(true, Some("+"), false) => ChordQuality::AugmentedSeventh,
+			(true, Some("+"), true) => ChordQuality::AugmentedMajorSeventh,
+			(false, None, false) => ChordQuality::MinorSeventh,
+			(false, None, true) => ChordQuality::MinorMajorSeventh,
+			(false, Some("°"), false) => ChordQuality::DiminishedSeventh,
+			(false, Some("ø"), false) => ChordQuality::HalfDiminishedSeventh,
+			_ => return None,
+		};
+		(quality, inversion)
+	} else {
+		let inversion = TRIAD_FIGURES.iter().position(|triad_figure| *triad_figure == figure)?;
+		let quality = match (is_upper_case, mark) {
+			(true, None) => ChordQuality::Major,
+			(true, Some("+")) => ChordQuality::Augmented,
+			(false, None) => ChordQuality::Minor,
+			(false, Some("°")) => ChordQuality::Diminished,
+			_ => return None,
+		};
+		(quality, inversion)
+	};
+	Some(RomanNumeral::new(degree, alteration, quality, inversion))
+}
+
+impl FromStr for RomanNumeral {
+	type Err = ParseError;
+
+	/*
+	 * Parses roman numerals like "V65", "bVI", "iv", "vii°7/V", "V/V/V", "N6", "It6", "Fr43" or "Ger65".
+	 * Upper case numerals are major, lower case numerals minor. The figures 7, 65, 43 and 42 (or 2) mark seventh
+	 * chords, 6 and 64 inverted triads.
+	 */
+	fn from_str(s: &str) -> Result<RomanNumeral, ParseError> {
+		let s = s.trim();
+		if s.is_empty() {
+			return Err(ParseError::Empty);
+		}
+		let invalid = || ParseError::InvalidRomanNumeral(s.to_string());
+		match s.find('/') {
+			Some(index) => {
+				let roman_numeral = parse_roman_numeral(&s[..index]).ok_or_else(invalid)?;
+				let target: RomanNumeral = s[index + 1..].parse().map_err(|_| invalid())?;
+				if roman_numeral.chromatic_chord.is_some() {
+					return Err(invalid());
+				}
+				Ok(roman_numeral.of(target))
+			},
+			None => parse_roman_numeral(s).ok_or_else(invalid),
+		}
+	}
+}
+
+impl fmt::Display for RomanNumeral {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.chromatic_chord {
+			Some(ChromaticChord::ItalianSixth) => return write!(f, "It6"),
+			Some(ChromaticChord::FrenchSixth) => return write!(f, "Fr43"),
+			Some(ChromaticChord::GermanSixth) => return write!(f, "Ger65"),
+			Some(ChromaticChord::Neapolitan) => return write!(f, "N{}", TRIAD_FIGURES.get(self.inversion).unwrap_or(&"")),
+			None => {},
+		}
+
+		let accidental = if self.alteration < 0 { "b" } else { "#" };
+		for _ in 0..self.alteration.abs() {
+			write!(f, "{}", accidental)?;
+		}
+		let numeral = NUMERALS[self.degree.rem_euclid(NUMERALS.len() as i32) as usize];
+		if has_major_third(self.quality) {
+			write!(f, "{}", numeral)?;
+		} else {
+			write!(f, "{}", numeral.to_ascii_lowercase())?;
+		}
+
+		let mark = match self.quality {
+			ChordQuality::Diminished | ChordQuality::DiminishedSeventh => "°",
+			ChordQuality::HalfDiminishedSeventh => "ø",
+			ChordQuality::Augmented | ChordQuality::AugmentedSeventh | ChordQuality::AugmentedMajorSeventh => "+",
+			ChordQuality::MajorSeventh | ChordQuality::MinorMajorSeventh => "M",
+			_ => "",
+		};
+		let major_seventh = if self.quality == ChordQuality::AugmentedMajorSeventh { "M" } else { "" };
+		let figures: &[&str] = if self.quality.is_seventh_chord() { &SEVENTH_CHORD_FIGURES } else { &TRIAD_FIGURES };
+		write!(f, "{}{}{}", mark, major_seventh, figures.get(self.inversion).unwrap_or(&""))?;
+
+		if let Some(target) = &self.target {
+			write!(f, "/{}", target)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn c_major() -> KeyScale {
+		KeyScale::from_scale_template(&ScaleTemplate::major(), Key::c())
+	}
+
+	fn keys(s: &str) -> Vec<Key> {
+		s.split_whitespace().map(|key| key.parse().unwrap()).collect()
+	}
+
+	#[test]
+	pub fn realize_roman_numerals() {
+		let chords = realize_progression("ii7 V7/V V I", &c_major()).unwrap();
+		assert_eq!(chords[0].keys, keys("d f a c"));
+		assert_eq!(chords[1].keys, keys("d f# a c"));
+		assert_eq!(chords[2].keys, keys("g b d"));
+		assert_eq!(chords[3].keys, keys("c e g"));
+
+		let chords = realize_progression("I6 V65 V42/IV vii°7/ii", &c_major()).unwrap();
+		assert_eq!(chords[0].keys, keys("e g c"));
+		assert_eq!(chords[1].keys, keys("b d f g"));
+		assert_eq!(chords[2].keys, keys("bb c e g"));
+		assert_eq!(chords[3].keys, keys("c# e g bb"));
+	}
+
+	#[test]
+	pub fn realize_chromatic_chords() {
+		let chords = realize_progression("bVI iv N6 It6 Fr43 Ger65", &c_major()).unwrap();
+		assert_eq!(chords[0].keys, keys("ab c eb"));
+		assert_eq!(chords[1].keys, keys("f ab c"));
+		assert_eq!(chords[2].keys, keys("f ab db"));
+		assert_eq!(chords[2].root, Key::cis());
+		assert_eq!(chords[3].keys, keys("ab c f#"));
+		assert_eq!(chords[4].keys, keys("ab c d f#"));
+		assert_eq!(chords[5].keys, keys("ab c eb f#"));
+
+		let a_minor = KeyScale::from_scale_template(&ScaleTemplate::minor(), Key::a());
+		let chords = realize_progression("i V vii°7 VI VII", &a_minor).unwrap();
+		assert_eq!(chords[1].keys, keys("e g# b"));
+		assert_eq!(chords[2].keys, keys("g# b d f"));
+		assert_eq!(chords[3].keys, keys("f a c"));
+		assert_eq!(chords[4].keys, keys("g b d"));
+	}
+
+	#[test]
+	pub fn parse_and_print_roman_numerals() {
+		for s in ["ii7", "V7/V", "vii°7/ii", "bVI", "N6", "Ger65", "IM7", "I64", "V43/IV", "III+", "viiø7", "V/V/V", "iM7"].iter() {
+			assert_eq!(s.parse::<RomanNumeral>().unwrap().to_string(), *s);
+		}
+		assert_eq!("viio7".parse::<RomanNumeral>().unwrap().to_string(), "vii°7");
+		assert_eq!("V2".parse::<RomanNumeral>().unwrap().to_string(), "V42");
+		assert_eq!("Imaj7".parse::<RomanNumeral>().unwrap().to_string(), "IM7");
+
+		for s in ["H", "IIII", "Vx", "iv+", "Iv", "V8", "Ger65/V", "bbbVI"].iter() {
+			assert_eq!(s.parse::<RomanNumeral>(), Err(ParseError::InvalidRomanNumeral(s.to_string())));
+		}
+		assert_eq!("bbVII".parse::<RomanNumeral>().unwrap().to_string(), "bbVII");
+		let many_flats = format!("{}VI", "b".repeat(130));
+		assert_eq!(many_flats.parse::<RomanNumeral>(), Err(ParseError::InvalidRomanNumeral(many_flats.clone())));
+		let many_sharps = format!("{}iv", "#".repeat(130));
+		assert_eq!(many_sharps.parse::<RomanNumeral>(), Err(ParseError::InvalidRomanNumeral(many_sharps.clone())));
+	}
+
+	#[test]
+	pub fn analyze_chords() {
+		let chords: Vec<KeyChord> = ["C", "Am", "Dm7", "D7", "G7/F", "Fm", "Ab", "Db/F", "E7", "F#dim7", "Bb"].iter()
+			.map(|symbol| symbol.parse().unwrap()).collect();
+		let labels: Vec<String> = analyze_progression(&chords, &c_major()).iter().map(|label| label.as_ref().unwrap().to_string()).collect();
+		assert_eq!(labels, vec!["I", "vi", "ii7", "V7/V", "V42", "iv", "bVI", "N6", "V7/vi", "vii°7/V", "bVII"]);
+
+		let german_sixth = realize_progression("Ger65", &c_major()).unwrap();
+		assert_eq!(RomanNumeral::analyze(&german_sixth[0], &c_major()), Some(RomanNumeral::augmented_sixth(ChromaticChord::GermanSixth)));
+
+		let a_minor = KeyScale::from_scale_template(&ScaleTemplate::minor(), Key::a());
+		let chords: Vec<KeyChord> = ["E", "G#dim7", "F"].iter().map(|symbol| symbol.parse().unwrap()).collect();
+		let labels: Vec<String> = analyze_progression(&chords, &a_minor).iter().map(|label| label.as_ref().unwrap().to_string()).collect();
+		assert_eq!(labels, vec!["V", "vii°7", "VI"]);
+
+		for (chord, label) in chords.iter().zip(analyze_progression(&chords, &a_minor)) {
+			assert_eq!(label.unwrap().to_key_chord(&a_minor).as_ref(), Some(chord));
+		}
+	}
+}
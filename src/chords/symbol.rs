@@ -0,0 +1,480 @@
+use std::collections::{BTreeSet, HashSet};
+use std::str::FromStr;
+use std::fmt;
+
+use crate::keys::Key;
+use crate::intervals::Interval;
+use crate::spelled_key::SpelledKey;
+use crate::parse::{ParseError, parse_note_name};
+use super::{ChordTemplate, KeyChord, chord_tone_rank};
+
+/*
+ * A lead-sheet chord symbol like "F#m7b5/C": a root, the chord template built on it and an optional
+ * bass key for slash chords.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChordSymbol {
+	pub root: SpelledKey,
+	pub chord_template: ChordTemplate,
+	pub bass: Option<SpelledKey>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Quality {
+	Major,
+	Minor,
+	Diminished,
+	HalfDiminished,
+	Augmented,
+}
+
+impl ChordSymbol {
+	pub fn new(root: SpelledKey, chord_template: ChordTemplate, bass: Option<SpelledKey>) -> ChordSymbol {
+		ChordSymbol { root, chord_template, bass }
+	}
+
+	/*
+	 * Returns the chord symbol of the given chord on the given root. All keys of the chord including the
+	 * bass are part of the chord template.
+	 */
+	pub fn from_key_chord(chord: &KeyChord, root: SpelledKey, bass: Option<SpelledKey>) -> ChordSymbol {
+		let intervals: HashSet<Interval> = chord.keys.iter().map(|key| Key::to_interval(root.to_key(), *key)).collect();
+		// a registered template keeps its compound intervals, like the ninth of C9
+		let registered = ChordTemplate::all().into_iter().map(|(_, chord_template)| chord_template).find(|chord_template| {
+			chord_template.intervals.iter().map(|interval| interval.to_base_interval()).collect::<HashSet<Interval>>() == intervals
+		});
+		let chord_template = registered.unwrap_or_else(|| {
+			let halftones: Vec<Interval> = compound_halftones(&ChordTemplate { intervals }).iter().map(|halftones| Interval { halftones: *halftones }).collect();
+			ChordTemplate::from_intervals(&halftones)
+		});
+		ChordSymbol { root, chord_template, bass }
+	}
+
+	/*
+	 * Returns the keys of this chord symbol with the bass as lowest key.
+	 */
+	pub fn to_key_chord(&self) -> KeyChord {
+		let chord = KeyChord::from_chord_template(&self.chord_template, self.root.to_key());
+		match self.bass {
+			Some(bass) => chord.with_bass(bass.to_key()),
+			None => chord,
+		}
+	}
+}
+
+/*
+ * Returns the intervals of the template in half tones, with the extensions of the chord as compound
+ * intervals: seconds and fourths above a third become ninths and elevenths, sixths above a seventh
+ * thirteenths and so on (see chord_tone_rank). The root is always part of the intervals.
+ */
+fn compound_halftones(chord_template: &ChordTemplate) -> BTreeSet<i32> {
+	let reduced: Vec<i32> = chord_template.intervals.iter().map(|interval| interval.to_base_interval().halftones).collect();
+	let mut halftones: BTreeSet<i32> = chord_template.intervals.iter().map(|interval| {
+		let base_halftones = interval.to_base_interval().halftones;
+		if interval.halftones < 12 && chord_tone_rank(base_halftones, &reduced) >= 4 { base_halftones + 12 } else { interval.halftones }
+	}).collect();
+	halftones.insert(0);
+	halftones
+}
+
+fn halftones_of(chord_template: &ChordTemplate) -> BTreeSet<i32> {
+	chord_template.intervals.iter().map(|interval| interval.halftones).collect()
+}
+
+/*
+ * Returns the modifiers, which turn the chord of the base symbol into the target chord, like "sus4" or
+ * "b9#11", or None if the parser has no modifiers for the difference.
+ */
+fn modifiers(base: &BTreeSet<i32>, target: &BTreeSet<i32>) -> Option<String> {
+	let mut symbol = String::new();
+	let mut base_rest = base.clone();
+	let mut target_rest = target.clone();
+	base_rest.remove(&0);
+	target_rest.remove(&0);
+
+	let base_third = [4, 3].iter().find(|third| base.contains(third)).cloned();
+	let target_third = [4, 3].iter().find(|third| target.contains(third)).cloned();
+	base_third.map(|third| base_rest.remove(&third));
+	target_third.map(|third| target_rest.remove(&third));
+	match (base_third, target_third) {
+		(Some(_), None) if target.contains(&2) => { symbol.push_str("sus2"); target_rest.remove(&2); }
+		(Some(_), None) if target.contains(&5) => { symbol.push_str("sus4"); target_rest.remove(&5); }
+		(Some(_), None) => symbol.push_str("no3"),
+		(base_third, target_third) if base_third != target_third => return None,
+		_ => {}
+	}
+
+	let base_fifth = [7, 6, 8].iter().find(|fifth| base.contains(fifth)).cloned();
+	let target_fifth = [7, 6, 8].iter().find(|fifth| target.contains(fifth)).cloned();
+	base_fifth.map(|fifth| base_rest.remove(&fifth));
+	target_fifth.map(|fifth| target_rest.remove(&fifth));
+	match (base_fifth, target_fifth) {
+		(Some(_), None) => symbol.push_str("no5"),
+		(Some(_), Some(6)) if base_fifth != target_fifth => symbol.push_str("b5"),
+		(Some(_), Some(8)) if base_fifth != target_fifth => symbol.push_str("#5"),
+		(base_fifth, target_fifth) if base_fifth != target_fifth => return None,
+		_ => {}
+	}
+
+	// the altered extensions replace the natural ones of the base symbol
+	let replacements: &[(i32, &[i32], &str)] = &[(14, &[13, 15], "add9"), (17, &[18], "add11"), (21, &[20], "add13")];
+	let mut missing: Vec<i32> = target_rest.difference(&base_rest).cloned().collect();
+	for extra in base_rest.difference(&target_rest) {
+		let (_, altered, _) = replacements.iter().find(|(natural, _, _)| natural == extra)?;
+		let index = missing.iter().position(|halftones| altered.contains(halftones))?;
+		symbol.push_str(modifier_name(missing.remove(index))?);
+	}
+	for halftones in missing {
+		symbol.push_str(modifier_name(halftones)?);
+		// a replacing modifier removes the natural extension, which has to be added again
+		if let Some((natural, _, add)) = replacements.iter().find(|(_, altered, _)| altered.contains(&halftones)) {
+			if target.contains(natural) && base.contains(natural) {
+				symbol.push_str(add);
+			}
+		}
+	}
+	Some(symbol)
+}
+
+fn modifier_name(halftones: i32) -> Option<&'static str> {
+	match halftones {
+		2 => Some("add2"),
+		5 => Some("add4"),
+		13 => Some("b9"),
+		14 => Some("add9"),
+		15 => Some("#9"),
+		17 => Some("add11"),
+		18 => Some("#11"),
+		20 => Some("b13"),
+		21 => Some("add13"),
+		_ => None,
+	}
+}
+
+/*
+ * Returns the shortest symbol the parser understands for exactly the given intervals.
+ */
+fn find_symbol(target: &BTreeSet<i32>) -> Option<String> {
+	let mut symbols = Vec::new();
+	for prefix in ["", "m", "maj", "mMaj"].iter() {
+		for extension in ["", "6", "6/9", "7", "9", "11", "13"].iter() {
+			let base_symbol = format!("{}{}", prefix, extension);
+			let base = match parse_quality(&base_symbol) {
+				Some(chord_template) => halftones_of(&chord_template),
+				None => continue,
+			};
+			if let Some(modifiers) = modifiers(&base, target) {
+				let symbol = base_symbol + &modifiers;
+				let is_exact = parse_quality(&symbol).map(|chord_template| halftones_of(&chord_template)).as_ref() == Some(target);
+				if is_exact {
+					symbols.push(symbol);
+				}
+			}
+		}
+	}
+	symbols.into_iter().min_by_key(|symbol| symbol.len())
+}
+
+/*
+ * Returns the canonical symbol of the given chord template without root, like "m7b5" or "7b9#11", which
+ * is understood by the chord symbol parser. Chord templates, which are not part of ChordTemplate::all(),
+ * are written as a quality and an extension with modifiers. Tones, which can not be written this way
+ * (like a major and a minor seventh together), are left out from the highest one down.
+ */
+pub fn chord_template_symbol(chord_template: &ChordTemplate) -> String {
+	let mut target = compound_halftones(chord_template);
+	let all = ChordTemplate::all();
+	let registered = all.iter().find(|(_, template)| template == chord_template)
+		.or_else(|| all.iter().find(|(_, template)| halftones_of(template) == target));
+	if let Some((symbol, _)) = registered {
+		return symbol.to_string();
+	}
+
+	loop {
+		if let Some(symbol) = find_symbol(&target) {
+			return symbol;
+		}
+		let highest = *target.iter().next_back().unwrap();
+		if highest == 0 {
+			return String::new();
+		}
+		target.remove(&highest);
+	}
+}
+
+/*
+ * Parses the part of a chord symbol after the root and before the bass, like "m7b5", "maj9#11" or "7(b9,#9)".
+ */
+fn parse_chord_template(s: &str) -> Option<ChordTemplate> {
+	// symbols of the registry, like "alt", "m(maj7)" or "+maj7", do not have to follow the rules below
+	if let Some(chord_template) = ChordTemplate::from_symbol(s) {
+		return Some(chord_template);
+	}
+
+	let mut rest = s;
+	let mut quality = Quality::Major;
+	let mut major_seventh = false;
+	let prefixes: &[(&str, Quality, bool)] = &[
+		("mMaj", Quality::Minor, true),
+		("mmaj", Quality::Minor, true),
+		("mM", Quality::Minor, true),
+		("maj", Quality::Major, true),
+		("Maj", Quality::Major, true),
+		("M", Quality::Major, true),
+		("Δ", Quality::Major, true),
+		("min", Quality::Minor, false),
+		("m", Quality::Minor, false),
+		("-", Quality::Minor, false),
+		("dim", Quality::Diminished, false),
+		("°", Quality::Diminished, false),
+		("o", Quality::Diminished, false),
+		("aug", Quality::Augmented, false),
+		("+", Quality::Augmented, false),
+		("ø", Quality::HalfDiminished, false),
+	];
+	if let Some((prefix, prefix_quality, prefix_major_seventh)) = prefixes.iter().find(|(prefix, _, _)| rest.starts_with(prefix)) {
+		rest = &rest[prefix.len()..];
+		quality = *prefix_quality;
+		major_seventh = *prefix_major_seventh;
+	}
+
+	let number_length = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+	let extension: u32 = if number_length == 0 {
+		// a lone major seventh or half diminished sign means a seventh chord
+		if (major_seventh && s.starts_with('Δ')) || quality == Quality::HalfDiminished { 7 } else { 0 }
+	} else {
+		rest[..number_length].parse().ok()?
+	};
+	rest = &rest[number_length..];
+
+	let mut third = Some(match quality {
+		Quality::Major | Quality::Augmented => 4,
+		_ => 3,
+	});
+	let mut fifth = Some(match quality {
+		Quality::Diminished | Quality::HalfDiminished => 6,
+		Quality::Augmented => 8,
+		_ => 7,
+	});
+	let seventh = if major_seventh {
+		11
+	} else if quality == Quality::Diminished {
+		9
+	} else {
+		10
+	};
+
+	let mut intervals: Vec<i32> = Vec::new();
+	match extension {
+		0 => {}
+		5 => third = None,
+		6 => intervals.push(9),
+		69 => intervals.extend(&[9, 14]),
+		7 => intervals.push(seventh),
+		9 => intervals.extend(&[seventh, 14]),
+		11 => intervals.extend(&[seventh, 14, 17]),
+		13 if quality == Quality::Minor => intervals.extend(&[seventh, 14, 17, 21]),
+		13 => intervals.extend(&[seventh, 14, 21]),
+		_ => return None,
+	}
+
+	let modifiers: &[&str] = &[
+		"sus2", "sus4", "sus", "add2", "add4", "add9", "add11", "add13", "no3", "no5",
+		"b5", "#5", "b9", "#9", "#11", "b13", "(", ")", ",", " ",
+	];
+	while !rest.is_empty() {
+		let modifier = *modifiers.iter().find(|modifier| rest.starts_with(*modifier))?;
+		rest = &rest[modifier.len()..];
+		match modifier {
+			"sus2" => third = Some(2),
+			"sus4" | "sus" => third = Some(5),
+			"add2" => intervals.push(2),
+			"add4" => intervals.push(5),
+			"add9" => intervals.push(14),
+			"add11" => intervals.push(17),
+			"add13" => intervals.push(21),
+			"no3" => third = None,
+			"no5" => fifth = None,
+			"b5" => fifth = Some(6),
+			"#5" => fifth = Some(8),
+			"b9" => { intervals.retain(|i| *i != 14); intervals.push(13) }
+			"#9" => { intervals.retain(|i| *i != 14); intervals.push(15) }
+			"#11" => { intervals.retain(|i| *i != 17); intervals.push(18) }
+			"b13" => { intervals.retain(|i| *i != 21); intervals.push(20) }
+			_ => {}
+		}
+	}
+
+	intervals.push(0);
+	intervals.extend(third);
+	intervals.extend(fifth);
+	Some(ChordTemplate::from_intervals(&intervals.iter().map(|halftones| Interval { halftones: *halftones }).collect::<Vec<Interval>>()))
+}
+
+/*
+ * Parses the quality of a chord symbol, which may be written with a slash like "6/9".
+ */
+fn parse_quality(s: &str) -> Option<ChordTemplate> {
+	parse_chord_template(&s.replace("6/9", "69"))
+}
+
+impl FromStr for ChordSymbol {
+	type Err = ParseError;
+
+	/*
+	 * Parses lead-sheet chord symbols like "C", "F#m7b5/C", "Bbmaj9#11", "Eb7(b9,#9)" or "Gsus4".
+	 */
+	fn from_str(s: &str) -> Result<ChordSymbol, ParseError> {
+		let trimmed = s.trim();
+		let invalid = || ParseError::InvalidChordSymbol(s.to_string());
+		let (root, rest) = parse_note_name(trimmed).map_err(|e| if e == ParseError::Empty { e } else { invalid() })?;
+
+		// the slash is only a bass, if it is followed by a note name (so "C6/9" has no bass)
+		let (quality, bass) = match rest.rfind('/') {
+			Some(index) => match rest[index + 1..].parse::<SpelledKey>() {
+				Ok(bass) => (&rest[..index], Some(bass)),
+				Err(_) => (rest, None),
+			},
+			None => (rest, None),
+		};
+		let chord_template = parse_quality(quality).ok_or_else(invalid)?;
+		Ok(ChordSymbol { root, chord_template, bass })
+	}
+}
+
+impl FromStr for KeyChord {
+	type Err = ParseError;
+
+	fn from_str(s: &str) -> Result<KeyChord, ParseError> {
+		s.parse::<ChordSymbol>().map(|chord_symbol| chord_symbol.to_key_chord())
+	}
+}
+
+/*
+ * Writes the chord as chord symbol on its root, with all keys as part of the chord template.
+ * So C/Bb is written as "C7/Bb". The root is spelled without context like SpelledKey::from_key, so the
+ * d-sharp minor chord is written as "Ebm"; use ChordSymbol::from_key_chord with a spelled root instead.
+ */
+impl fmt::Display for KeyChord {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let root = SpelledKey::from_key(self.root);
+		let bass = self.bass();
+		let bass = if bass == self.root { None } else { Some(root.above_interval(Key::to_interval(self.root, bass))) };
+		ChordSymbol::from_key_chord(self, root, bass).fmt(f)
+	}
+}
+
+/*
+ * Writes the canonical chord symbol, with an upper case root like "F#m7b5/C".
+ */
+impl fmt::Display for ChordSymbol {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let root = self.root.to_string();
+		write!(f, "{}{}{}", root[..1].to_uppercase(), &root[1..], chord_template_symbol(&self.chord_template))?;
+		if let Some(bass) = self.bass {
+			let bass = bass.to_string();
+			write!(f, "/{}{}", bass[..1].to_uppercase(), &bass[1..])?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::spelled_key::Letter;
+	use crate::chords::CHORD_TEMPLATE_ALIASES;
+
+	#[test]
+	pub fn parse_chord_symbols() {
+		let chord_symbol: ChordSymbol = "F#m7b5/C".parse().unwrap();
+		assert_eq!(chord_symbol.root, SpelledKey::new(Letter::F, 1));
+		assert_eq!(chord_symbol.chord_template, ChordTemplate::half_diminished_seventh());
+		assert_eq!(chord_symbol.bass, Some(SpelledKey::natural(Letter::C)));
+
+		// c is already the diminished fifth of f-sharp
+		let chord: KeyChord = "F#m7b5/C".parse().unwrap();
+		assert_eq!(chord.keys.len(), 4);
+		let chord: KeyChord = "C/Bb".parse().unwrap();
+		assert_eq!(chord.keys, vec![Key::ais(), Key::c(), Key::e(), Key::g()]);
+		let chord: KeyChord = "C7/E".parse().unwrap();
+		assert_eq!(chord.inversion(), 1);
+
+		assert_eq!("Cmaj7".parse::<ChordSymbol>().unwrap().chord_template, ChordTemplate::major_seventh());
+		assert_eq!("CΔ".parse::<ChordSymbol>().unwrap().chord_template, ChordTemplate::major_seventh());
+		assert_eq!("Bbø".parse::<ChordSymbol>().unwrap().chord_template, ChordTemplate::half_diminished_seventh());
+		assert_eq!("G7(b9,#9)".parse::<ChordSymbol>().unwrap().chord_template, ChordTemplate::from_intervals(
+			&[Interval::prime(), Interval::major_third(), Interval::fifth(), Interval::minor_seventh(), Interval::minor_ninth(), Interval::minor_tenth()]));
+		assert_eq!("C6/9".parse::<ChordSymbol>().unwrap().bass, None);
+		assert_eq!("C6/9".parse::<ChordSymbol>().unwrap().chord_template.intervals.len(), 5);
+		assert_eq!("Ebsus".parse::<ChordSymbol>().unwrap().chord_template, ChordTemplate::suspended_fourth());
+
+		assert_eq!("H7".parse::<ChordSymbol>(), Err(ParseError::InvalidChordSymbol("H7".to_string())));
+		assert_eq!("C7x".parse::<ChordSymbol>(), Err(ParseError::InvalidChordSymbol("C7x".to_string())));
+		assert_eq!("C15".parse::<ChordSymbol>(), Err(ParseError::InvalidChordSymbol("C15".to_string())));
+	}
+
+	#[test]
+	pub fn parse_all_chord_template_symbols() {
+		let aliases = CHORD_TEMPLATE_ALIASES.iter().map(|(symbol, chord_template)| (*symbol, chord_template()));
+		for (symbol, chord_template) in ChordTemplate::all().into_iter().chain(aliases) {
+			let chord_symbol: ChordSymbol = format!("C{}", symbol).parse().unwrap();
+			assert_eq!(chord_symbol.chord_template, chord_template, "{}", symbol);
+		}
+		assert_eq!("Cm(maj7)".parse::<ChordSymbol>().unwrap().chord_template, ChordTemplate::minor_major_seventh());
+		assert_eq!("C+maj7/E".parse::<ChordSymbol>().unwrap().chord_template, ChordTemplate::augmented_major_seventh());
+	}
+
+	#[test]
+	pub fn print_chord_symbols() {
+		for symbol in ["C", "F#m7b5/C", "Bbmaj7", "Ebm", "G7b9", "Ab13", "Dsus4", "Gb7alt"].iter() {
+			assert_eq!(symbol.parse::<ChordSymbol>().unwrap().to_string(), *symbol);
+		}
+		assert_eq!("Cmin7".parse::<ChordSymbol>().unwrap().to_string(), "Cm7");
+		assert_eq!("C7(b9,#11)".parse::<ChordSymbol>().unwrap().to_string(), "C7b9#11");
+		assert_eq!("Cmaj7#11".parse::<ChordSymbol>().unwrap().to_string(), "Cmaj7#11");
+		assert_eq!("C6/9".parse::<ChordSymbol>().unwrap().to_string(), "C6/9");
+		assert_eq!("C7b5".parse::<ChordSymbol>().unwrap().to_string(), "C7b5");
+		assert_eq!("C9sus4".parse::<ChordSymbol>().unwrap().to_string(), "C9sus4");
+		assert_eq!("Cm7b9".parse::<ChordSymbol>().unwrap().to_string(), "Cm7b9");
+
+		// extended chords keep their compound intervals
+		for symbol in ["C9", "Cmaj9", "C11", "Cm13", "C7alt", "C13#11"].iter() {
+			assert_eq!(symbol.parse::<KeyChord>().unwrap().to_string(), *symbol);
+		}
+
+		let chord = KeyChord::from_chord_template(&ChordTemplate::minor_seventh(), Key::a());
+		let chord_symbol = ChordSymbol::from_key_chord(&chord, SpelledKey::natural(Letter::A), None);
+		assert_eq!(chord_symbol.to_string(), "Am7");
+		let chord_symbol = ChordSymbol::from_key_chord(&chord, SpelledKey::natural(Letter::C), None);
+		assert_eq!(chord_symbol.to_string(), "C6");
+		let chord_symbol = ChordSymbol::from_key_chord(&chord, SpelledKey::natural(Letter::A), Some(SpelledKey::natural(Letter::G)));
+		assert_eq!(chord_symbol.to_string(), "Am7/G");
+
+		assert_eq!(chord.invert(3).to_string(), "Am7/G");
+		assert_eq!("Ebmaj7/G".parse::<KeyChord>().unwrap().to_string(), "Ebmaj7/G");
+		assert_eq!("C/Bb".parse::<KeyChord>().unwrap().to_string(), "C7/Bb");
+		// a key chord does not remember the spelling of its root
+		assert_eq!("D#m".parse::<KeyChord>().unwrap().to_string(), "Ebm");
+	}
+
+	#[test]
+	pub fn print_all_chord_template_symbols() {
+		for root in ["C", "F#", "Bb", "Eb", "B"].iter() {
+			for (symbol, chord_template) in ChordTemplate::all() {
+				let chord_symbol: ChordSymbol = format!("{}{}", root, symbol).parse().unwrap();
+				let printed = chord_symbol.to_string();
+				assert_eq!(printed.parse::<ChordSymbol>().unwrap().chord_template, chord_template, "{}", printed);
+
+				let chord = chord_symbol.to_key_chord();
+				let printed = chord.to_string();
+				assert_eq!(printed.parse::<KeyChord>().unwrap().keys, chord.keys, "{}", printed);
+			}
+		}
+
+		// chords without a symbol of their own are written with modifiers, which can be parsed again
+		let chord_template = ChordTemplate::from_intervals(&[Interval::prime(), Interval::major_third(), Interval::minor_seventh(), Interval::major_seventh()]);
+		let printed = ChordSymbol::new(SpelledKey::natural(Letter::C), chord_template, None).to_string();
+		assert!(printed.parse::<ChordSymbol>().is_ok(), "{}", printed);
+	}
+}
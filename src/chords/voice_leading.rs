@@ -0,0 +1,221 @@
+use crate::keys::Key;
+use crate::intervals::Interval;
+use crate::pitch::{Pitch, PitchRange};
+use super::{KeyChord, PitchChord};
+use super::voicing::free_voicings;
+
+/*
+ * Every broken rule costs more than any amount of movement, so rules are only broken, if there is no
+ * way around it.
+ */
+const RULE_VIOLATION_COST: u32 = 1000;
+
+/*
+ * The rules, which are followed when connecting two voicings.
+ * Leading tones are keys a half tone below the root of the next chord in a chord on its dominant (or on
+ * the leading tone itself); they have to move up to the root.
+ * Common tones are keys of both chords, which have to stay on their pitch in all voices but the bass.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VoiceLeadingRules {
+	pub forbid_parallel_fifths_and_octaves: bool,
+	pub resolve_leading_tones: bool,
+	pub keep_common_tones: bool,
+}
+
+impl VoiceLeadingRules {
+	pub fn strict() -> VoiceLeadingRules {
+		VoiceLeadingRules { forbid_parallel_fifths_and_octaves: true, resolve_leading_tones: true, keep_common_tones: true }
+	}
+
+	/*
+	 * Only minimizes the movement of the voices.
+	 */
+	pub fn free() -> VoiceLeadingRules {
+		VoiceLeadingRules { forbid_parallel_fifths_and_octaves: false, resolve_leading_tones: false, keep_common_tones: false }
+	}
+}
+
+/*
+ * Returns the number of voice pairs, which move in parallel fifths or octaves (including unisons).
+ */
+fn parallel_fifths_and_octaves(from: &[Pitch], to: &[Pitch]) -> u32 {
+	let mut count = 0;
+	for lower in 0..from.len() {
+		for upper in lower + 1..from.len() {
+			let interval_before = (from[upper].value - from[lower].value).rem_euclid(12);
+			let interval_after = (to[upper].value - to[lower].value).rem_euclid(12);
+			let lower_motion = to[lower].value - from[lower].value;
+			let upper_motion = to[upper].value - from[upper].value;
+			let is_similar_motion = lower_motion.signum() == upper_motion.signum() && lower_motion != 0;
+			if is_similar_motion && interval_before == interval_after && (interval_before == 0 || interval_before == 7) {
+				count += 1;
+			}
+		}
+	}
+	count
+}
+
+/*
+ * Returns the leading tone of the next chord, if the chord leads to it: the chord is on the dominant
+ * or on the leading tone of the next chord and contains the leading tone.
+ */
+fn leading_tone(chord: &KeyChord, next_chord: &KeyChord) -> Option<Key> {
+	let leading_tone = next_chord.root - Interval::minor_second();
+	let is_dominant = Key::to_interval(next_chord.root, chord.root) == Interval::fifth() || chord.root == leading_tone;
+	if is_dominant && chord.keys.contains(&leading_tone) {
+		Some(leading_tone)
+	} else {
+		None
+	}
+}
+
+/*
+ * The cost of moving from one voicing of the first chord to a voicing of the second chord: the sum of
+ * the movement of all voices in half tones plus the cost of all broken rules.
+ */
+fn transition_cost(from: &[Pitch], to: &[Pitch], chord: &KeyChord, next_chord: &KeyChord, rules: VoiceLeadingRules) -> u32 {
+	let mut cost: u32 = from.iter().zip(to.iter()).map(|(from, to)| (to.value - from.value).unsigned_abs()).sum();
+
+	if rules.forbid_parallel_fifths_and_octaves {
+		cost += RULE_VIOLATION_COST * parallel_fifths_and_octaves(from, to);
+	}
+	if rules.resolve_leading_tones {
+		if let Some(leading_tone) = leading_tone(chord, next_chord) {
+			let unresolved = from.iter().zip(to.iter())
+				.filter(|(from, to)| Key::from(**from) == leading_tone && to.value != from.value + 1)
+				.count();
+			cost += RULE_VIOLATION_COST * unresolved as u32;
+		}
+	}
+	if rules.keep_common_tones {
+		let moved = from.iter().zip(to.iter()).skip(1)
+			.filter(|(from, to)| next_chord.keys.contains(&Key::from(**from)) && from != to)
+			.count();
+		cost += RULE_VIOLATION_COST * moved as u32;
+	}
+	cost
+}
+
+/*
+ * The cost of the whole sequence of voicings of the given chords.
+ */
+pub(crate) fn total_cost(voicings: &[PitchChord], chords: &[KeyChord], rules: VoiceLeadingRules) -> u32 {
+	(1..voicings.len().min(chords.len()))
+		.map(|index| transition_cost(&voicings[index - 1].pitches, &voicings[index].pitches, &chords[index - 1], &chords[index], rules))
+		.sum()
+}
+
+impl PitchChord {
+	/*
+	 * Voices the given chords one after another, starting with this voicing of the first chord, so that
+	 * the voices move as little as possible while following the rules. Every voice stays in its pitch
+	 * range and the bass of every chord is in the lowest voice.
+	 * Returns a voicing for every chord including the first one, or None if a chord can not be voiced in
+	 * the ranges or the number of ranges does not match the number of voices.
+	 */
+	pub fn lead_voices(&self, chords: &[KeyChord], ranges: &[PitchRange], rules: VoiceLeadingRules) -> Option<Vec<PitchChord>> {
+		if ranges.len() != self.pitches.len() || chords.is_empty() {
+			return None;
+		}
+
+		// for every candidate voicing of the current chord: its voicing, the cost to reach it and the index of its predecessor
+		let mut steps: Vec<Vec<(Vec<Pitch>, u32, usize)>> = vec![vec![(self.pitches.clone(), 0, 0)]];
+		for (chord, next_chord) in chords.iter().zip(chords.iter().skip(1)) {
+			let previous_step = &steps[steps.len() - 1];
+			let candidates = free_voicings(next_chord, ranges);
+			if candidates.is_empty() {
+				return None;
+			}
+			let step = candidates.into_iter().map(|candidate| {
+				let (predecessor, cost) = previous_step.iter().enumerate()
+					.map(|(index, (pitches, cost, _))| (index, cost + transition_cost(pitches, &candidate, chord, next_chord, rules)))
+					.min_by_key(|(_, cost)| *cost)
+					.unwrap();
+				(candidate, cost, predecessor)
+			}).collect();
+			steps.push(step);
+		}
+
+		let last_step = &steps[steps.len() - 1];
+		let (mut index, _) = last_step.iter().enumerate().min_by_key(|(_, (_, cost, _))| *cost)?;
+		let mut voicings = Vec::new();
+		for step in steps.iter().rev() {
+			let (pitches, _, predecessor) = &step[index];
+			voicings.push(PitchChord { pitches: pitches.clone() });
+			index = *predecessor;
+		}
+		voicings.reverse();
+		Some(voicings)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::chords::ChordTemplate;
+	use crate::chords::voicing::satb_ranges;
+
+	fn pitches(values: &[i32]) -> Vec<Pitch> {
+		values.iter().map(|value| Pitch { value: *value }).collect()
+	}
+
+	fn total_movement(voicings: &[PitchChord]) -> u32 {
+		voicings.windows(2).map(|pair| pair[0].pitches.iter().zip(pair[1].pitches.iter())
+			.map(|(from, to)| (to.value - from.value).unsigned_abs()).sum::<u32>()).sum()
+	}
+
+	#[test]
+	pub fn lead_voices_through_cadence() {
+		let chords = vec![
+			KeyChord::from_chord_template(&ChordTemplate::major(), Key::c()),
+			KeyChord::from_chord_template(&ChordTemplate::major(), Key::f()),
+			KeyChord::from_chord_template(&ChordTemplate::dominant_seventh(), Key::g()),
+			KeyChord::from_chord_template(&ChordTemplate::major(), Key::c()),
+		];
+		// c3 g3 e4 c5
+		let start = PitchChord { pitches: pitches(&[27, 34, 43, 51]) };
+		let voicings = start.lead_voices(&chords, &satb_ranges(), VoiceLeadingRules::strict()).unwrap();
+		assert_eq!(voicings.len(), 4);
+		assert_eq!(voicings[0], start);
+
+		for (voicing, chord) in voicings.iter().zip(chords.iter()) {
+			assert_eq!(Key::from(voicing.pitches[0]), chord.bass());
+			for (pitch, range) in voicing.pitches.iter().zip(satb_ranges().iter()) {
+				assert!(range.lower <= *pitch && *pitch < range.upper);
+			}
+		}
+		for pair in voicings.windows(2) {
+			assert_eq!(parallel_fifths_and_octaves(&pair[0].pitches, &pair[1].pitches), 0);
+		}
+
+		// the c of the soprano is kept from c major to f major
+		assert_eq!(voicings[1].pitches[3], Pitch { value: 51 });
+		// the leading tone b of g seventh resolves to c
+		for (from, to) in voicings[2].pitches.iter().zip(voicings[3].pitches.iter()) {
+			if Key::from(*from) == Key::b() {
+				assert_eq!(to.value, from.value + 1);
+			}
+		}
+
+		let free_voicings = start.lead_voices(&chords, &satb_ranges(), VoiceLeadingRules::free()).unwrap();
+		assert!(total_movement(&free_voicings) <= total_movement(&voicings));
+	}
+
+	#[test]
+	pub fn detect_parallel_fifths() {
+		// c3 g3 to d3 a3
+		assert_eq!(parallel_fifths_and_octaves(&pitches(&[27, 34]), &pitches(&[29, 36])), 1);
+		// c3 g3 to d3 g3 is oblique motion
+		assert_eq!(parallel_fifths_and_octaves(&pitches(&[27, 34]), &pitches(&[29, 34])), 0);
+		// c3 c4 to d3 d4
+		assert_eq!(parallel_fifths_and_octaves(&pitches(&[27, 39]), &pitches(&[29, 41])), 1);
+	}
+
+	#[test]
+	pub fn lead_voices_with_wrong_number_of_ranges() {
+		let chords = vec![KeyChord::from_chord_template(&ChordTemplate::major(), Key::c())];
+		let start = PitchChord { pitches: pitches(&[27, 34, 43]) };
+		assert_eq!(start.lead_voices(&chords, &satb_ranges(), VoiceLeadingRules::strict()), None);
+	}
+}
@@ -0,0 +1,293 @@
+use crate::keys::Key;
+use crate::intervals::Interval;
+use crate::pitch::{Pitch, PitchRange};
+use super::{KeyChord, PitchChord};
+
+/*
+ * The ways to distribute the keys of a chord on the voices.
+ * Close and Open describe the upper voices (all voices above the bass) like in four-part writing:
+ * in close position no chord key fits between two neighboring upper voices, in open position exactly one.
+ * Drop2 and Drop3 are close voicings of all voices, in which the second or third voice from the top is
+ * moved down by an octave into the bass.
+ * Shell voicings only use the bass, the third and the seventh (or sixth) with the upper voices within an octave.
+ * Quartal voicings stack keys of the chord in fourths above the bass.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VoicingStyle {
+	Close,
+	Open,
+	Drop2,
+	Drop3,
+	Shell,
+	Quartal,
+}
+
+/*
+ * The usual ranges of bass, tenor, alto and soprano: e2-c4, c3-g4, g3-d5 and c4-g5.
+ */
+pub fn satb_ranges() -> Vec<PitchRange> {
+	// the upper bounds of pitch ranges are exclusive
+	vec![
+		PitchRange { lower: Pitch { value: 19 }, upper: Pitch { value: 40 } },
+		PitchRange { lower: Pitch { value: 27 }, upper: Pitch { value: 47 } },
+		PitchRange { lower: Pitch { value: 34 }, upper: Pitch { value: 54 } },
+		PitchRange { lower: Pitch { value: 39 }, upper: Pitch { value: 59 } },
+	]
+}
+
+/*
+ * The order in which keys are left out, if a chord has more keys than voices, by their interval above
+ * the root: the fifth first, then the root, the extensions and at last the third and the seventh.
+ */
+const OMISSION_ORDER: [i32; 12] = [7, 0, 2, 5, 9, 1, 8, 6, 3, 4, 10, 11];
+
+/*
+ * Returns the keys of the chord, which are used for the given number of voices. The bass is always kept.
+ */
+fn select_keys(chord: &KeyChord, voices: usize) -> Vec<Key> {
+	let bass = chord.bass();
+	let mut keys = chord.keys.clone();
+	for halftones in OMISSION_ORDER.iter() {
+		if keys.len() <= voices.max(1) {
+			break;
+		}
+		keys.retain(|key| *key == bass || Key::to_interval(chord.root, *key).halftones != *halftones);
+	}
+	keys
+}
+
+/*
+ * Returns the bass, the third and the seventh of the chord. Chords without seventh use their sixth
+ * and chords without third use their suspended second or fourth.
+ */
+fn shell_keys(chord: &KeyChord) -> Vec<Key> {
+	let find = |halftones: &[i32]| halftones.iter()
+		.map(|halftones| chord.root + Interval { halftones: *halftones })
+		.find(|key| chord.keys.contains(key));
+
+	let mut keys = vec![chord.bass()];
+	let guide_keys = [find(&[3, 4, 5, 2]), find(&[10, 11, 9])];
+	for key in guide_keys.iter().flatten() {
+		if !keys.contains(key) {
+			keys.push(*key);
+		}
+	}
+	keys
+}
+
+/*
+ * Returns the number of pitches between the two pitches, which have one of the given keys.
+ */
+fn keys_between(lower: Pitch, upper: Pitch, keys: &[Key]) -> usize {
+	(lower.value + 1..upper.value).filter(|value| keys.contains(&Key::from(Pitch { value: *value }))).count()
+}
+
+fn is_close(pitches: &[Pitch], keys: &[Key]) -> bool {
+	pitches.windows(2).all(|pair| keys_between(pair[0], pair[1], keys) == 0)
+}
+
+/*
+ * Checks, whether moving the bass up by an octave results in a close voicing, in which the former bass
+ * is the given number of voices below the top.
+ */
+fn is_drop(pitches: &[Pitch], keys: &[Key], voice_from_top: usize) -> bool {
+	if pitches.len() <= voice_from_top {
+		return false;
+	}
+	let raised = pitches[0] + Interval::octave();
+	let mut close_pitches = pitches[1..].to_vec();
+	if close_pitches.contains(&raised) {
+		return false;
+	}
+	close_pitches.push(raised);
+	close_pitches.sort();
+	close_pitches[pitches.len() - voice_from_top] == raised && is_close(&close_pitches, keys)
+}
+
+fn matches_style(pitches: &[Pitch], keys: &[Key], style: VoicingStyle) -> bool {
+	let upper_pitches = &pitches[1..];
+	match style {
+		VoicingStyle::Close => is_close(upper_pitches, keys),
+		VoicingStyle::Open => upper_pitches.windows(2).all(|pair| keys_between(pair[0], pair[1], keys) == 1),
+		VoicingStyle::Drop2 => is_drop(pitches, keys, 2),
+		VoicingStyle::Drop3 => is_drop(pitches, keys, 3),
+		VoicingStyle::Shell => match (upper_pitches.first(), upper_pitches.last()) {
+			(Some(lowest), Some(highest)) => highest.value - lowest.value < 12,
+			_ => true,
+		},
+		VoicingStyle::Quartal => pitches.windows(2).all(|pair| pair[1].value - pair[0].value == 5 || pair[1].value - pair[0].value == 6),
+	}
+}
+
+/*
+ * Collects all ascending pitch sequences with one pitch per voice, where every pitch has one of the allowed keys.
+ */
+fn collect_voicings(ranges: &[PitchRange], allowed_keys: &[Key], bass: Key, pitches: &mut Vec<Pitch>, voicings: &mut Vec<Vec<Pitch>>) {
+	let voice = pitches.len();
+	if voice == ranges.len() {
+		voicings.push(pitches.clone());
+		return;
+	}
+	let lowest = match pitches.last() {
+		Some(previous) => ranges[voice].lower.value.max(previous.value + 1),
+		None => ranges[voice].lower.value,
+	};
+	for value in lowest..ranges[voice].upper.value {
+		let pitch = Pitch { value };
+		let key = Key::from(pitch);
+		let is_allowed = if voice == 0 { key == bass } else { allowed_keys.contains(&key) };
+		if is_allowed {
+			pitches.push(pitch);
+			collect_voicings(ranges, allowed_keys, bass, pitches, voicings);
+			pitches.pop();
+		}
+	}
+}
+
+/*
+ * Doubling the root is preferred over doubling the fifth, which is preferred over doubling other keys.
+ */
+fn doubling_cost(pitches: &[Pitch], root: Key) -> u32 {
+	let mut keys: Vec<Key> = Vec::new();
+	let mut cost = 0;
+	for pitch in pitches {
+		let key = Key::from(*pitch);
+		if keys.contains(&key) {
+			cost += match Key::to_interval(root, key).halftones {
+				0 => 0,
+				7 => 1,
+				_ => 2,
+			};
+		} else {
+			keys.push(key);
+		}
+	}
+	cost
+}
+
+/*
+ * Returns all voicings with one voice per pitch range, which only use the given keys and contain all required keys.
+ */
+fn candidate_voicings(chord: &KeyChord, keys: &[Key], required_keys: &[Key], ranges: &[PitchRange]) -> Vec<Vec<Pitch>> {
+	let mut voicings = Vec::new();
+	collect_voicings(ranges, keys, chord.bass(), &mut Vec::new(), &mut voicings);
+	voicings.retain(|pitches| required_keys.iter().all(|key| pitches.iter().any(|pitch| Key::from(*pitch) == *key)));
+	voicings
+}
+
+/*
+ * Returns all voicings of the chord with one voice per pitch range regardless of the style,
+ * with the same choice of keys as PitchChord::voicings.
+ */
+pub(crate) fn free_voicings(chord: &KeyChord, ranges: &[PitchRange]) -> Vec<Vec<Pitch>> {
+	if ranges.is_empty() || chord.keys.is_empty() {
+		return Vec::new();
+	}
+	let keys = select_keys(chord, ranges.len());
+	candidate_voicings(chord, &keys, &keys, ranges)
+}
+
+impl PitchChord {
+	/*
+	 * Returns all voicings of the chord in the given style with one voice per pitch range, from the lowest
+	 * voice to the highest. The bass of the chord is always in the lowest voice. If there are more keys than
+	 * voices, keys are left out (the fifth first), if there are more voices than keys, keys are doubled.
+	 * The voicings are ordered by preference: fewer and better doublings first, then narrower voicings.
+	 */
+	pub fn voicings(chord: &KeyChord, style: VoicingStyle, ranges: &[PitchRange]) -> Vec<PitchChord> {
+		if ranges.is_empty() || chord.keys.is_empty() {
+			return Vec::new();
+		}
+		let (keys, required_keys) = match style {
+			VoicingStyle::Shell => {
+				let keys = select_keys(&KeyChord::from_keys(chord.root, &shell_keys(chord)), ranges.len());
+				(keys.clone(), keys)
+			},
+			VoicingStyle::Quartal => (chord.keys.clone(), vec![chord.bass()]),
+			_ => {
+				let keys = select_keys(chord, ranges.len());
+				(keys.clone(), keys)
+			},
+		};
+
+		let mut voicings = candidate_voicings(chord, &keys, &required_keys, ranges);
+		voicings.retain(|pitches| matches_style(pitches, &keys, style));
+		voicings.sort_by_key(|pitches| (doubling_cost(pitches, chord.root), pitches[pitches.len() - 1].value - pitches[0].value, pitches.clone()));
+		voicings.into_iter().map(|pitches| PitchChord { pitches }).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::chords::ChordTemplate;
+
+	fn pitches(values: &[i32]) -> Vec<Pitch> {
+		values.iter().map(|value| Pitch { value: *value }).collect()
+	}
+
+	fn piano_ranges(voices: usize) -> Vec<PitchRange> {
+		vec![PitchRange { lower: Pitch { value: 27 }, upper: Pitch { value: 51 } }; voices]
+	}
+
+	#[test]
+	pub fn close_and_open_voicings() {
+		let c_major = KeyChord::from_chord_template(&ChordTemplate::major(), Key::c());
+		let voicings = PitchChord::voicings(&c_major, VoicingStyle::Close, &satb_ranges());
+		assert!(!voicings.is_empty());
+		for voicing in voicings.iter() {
+			assert_eq!(Key::from(voicing.pitches[0]), Key::c());
+			assert!(voicing.pitches[3].value - voicing.pitches[1].value < 12);
+		}
+		// c3 c4 e4 g4 with the doubled root
+		assert!(voicings.contains(&PitchChord { pitches: pitches(&[27, 39, 43, 46]) }));
+
+		let voicings = PitchChord::voicings(&c_major, VoicingStyle::Open, &satb_ranges());
+		// c3 g3 e4 c5
+		assert!(voicings.contains(&PitchChord { pitches: pitches(&[27, 34, 43, 51]) }));
+		assert!(!voicings.contains(&PitchChord { pitches: pitches(&[27, 39, 43, 46]) }));
+
+		let first_inversion = c_major.invert(1);
+		for voicing in PitchChord::voicings(&first_inversion, VoicingStyle::Close, &satb_ranges()) {
+			assert_eq!(Key::from(voicing.pitches[0]), Key::e());
+		}
+	}
+
+	#[test]
+	pub fn drop_voicings() {
+		let c_seventh = KeyChord::from_chord_template(&ChordTemplate::major_seventh(), Key::c());
+		// c3 g3 b3 e4 is the drop 2 voicing of g3 b3 c4 e4
+		let voicings = PitchChord::voicings(&c_seventh, VoicingStyle::Drop2, &piano_ranges(4));
+		assert_eq!(voicings[0].pitches, pitches(&[27, 34, 38, 43]));
+		// c3 b3 e4 g4 is the drop 3 voicing of b3 c4 e4 g4
+		let voicings = PitchChord::voicings(&c_seventh, VoicingStyle::Drop3, &piano_ranges(4));
+		assert_eq!(voicings[0].pitches, pitches(&[27, 38, 43, 46]));
+	}
+
+	#[test]
+	pub fn shell_and_quartal_voicings() {
+		let c_thirteenth = KeyChord::from_chord_template(&ChordTemplate::dominant_thirteenth(), Key::c());
+		let voicings = PitchChord::voicings(&c_thirteenth, VoicingStyle::Shell, &piano_ranges(3));
+		assert_eq!(voicings[0].pitches, pitches(&[27, 31, 37]));
+
+		let c_minor_eleventh = KeyChord::from_chord_template(&ChordTemplate::minor_eleventh(), Key::c());
+		let voicings = PitchChord::voicings(&c_minor_eleventh, VoicingStyle::Quartal, &piano_ranges(4));
+		// c3 f3 bb3 eb4
+		assert_eq!(voicings[0].pitches, pitches(&[27, 32, 37, 42]));
+
+		let c_major = KeyChord::from_chord_template(&ChordTemplate::major(), Key::c());
+		assert!(PitchChord::voicings(&c_major, VoicingStyle::Quartal, &piano_ranges(3)).is_empty());
+	}
+
+	#[test]
+	pub fn voicings_leave_out_the_fifth() {
+		let c_ninth = KeyChord::from_chord_template(&ChordTemplate::dominant_ninth(), Key::c());
+		let voicings = PitchChord::voicings(&c_ninth, VoicingStyle::Close, &satb_ranges());
+		assert!(!voicings.is_empty());
+		for voicing in voicings {
+			let keys: Vec<Key> = voicing.pitches.iter().map(|pitch| Key::from(*pitch)).collect();
+			assert!(!keys.contains(&Key::g()));
+			assert!(keys.contains(&Key::e()) && keys.contains(&Key::ais()) && keys.contains(&Key::d()));
+		}
+	}
+}
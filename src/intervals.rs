@@ -1,5 +1,7 @@
 use std::fmt;
 
+use crate::keys::Key;
+use crate::spelled_key::SpelledKey;
 use crate::math::neg_modulo;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -46,6 +48,26 @@ impl Interval {
 	pub fn get_num_octaves(self) -> i32 {
 		self.halftones / Interval::octave().halftones
 	}
+
+	/*
+	 * Returns the number of diatonic steps this interval usually spans.
+	 * The tritone is treated as diminished fifth.
+	 */
+	pub fn to_unspecified_interval(self) -> UnspecifiedInterval {
+		let base_interval = self.to_base_interval();
+		let num_octaves = (self.halftones - base_interval.halftones) / Interval::octave().halftones;
+		let steps = match base_interval.halftones {
+			0 => 0,
+			1 | 2 => 1,
+			3 | 4 => 2,
+			5 => 3,
+			6 | 7 => 4,
+			8 | 9 => 5,
+			10 | 11 => 6,
+			_ => unimplemented!(),
+		};
+		UnspecifiedInterval { value: steps + num_octaves * UnspecifiedInterval::octave().value }
+	}
 }
 
 impl fmt::Display for Interval {
@@ -102,6 +124,14 @@ impl UnspecifiedInterval {
 		self.value / UnspecifiedInterval::octave().value
 	}
 
+	/*
+	 * Primes, fourths, fifths and their octave equivalents are perfect intervals,
+	 * all others are major or minor.
+	 */
+	pub fn is_perfect(self) -> bool {
+		matches!(self.to_base_unspecified_interval().value, 0 | 3 | 4)
+	}
+
 	pub fn to_minor_or_perfect(self) -> Interval {
 		let mut interval: Interval = match self.to_base_unspecified_interval().value {
 			0 => Interval::prime(),
@@ -148,9 +178,150 @@ impl fmt::Display for UnspecifiedInterval {
 	}
 }
 
+/*
+ * The quality of an interval. Augmented and Diminished contain how often the interval is
+ * augmented or diminished, so Augmented(2) is doubly augmented.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IntervalQuality {
+	Perfect,
+	Major,
+	Minor,
+	Augmented(u8),
+	Diminished(u8),
+}
+
+/*
+ * An interval with its diatonic number and quality, so the augmented fourth and the diminished fifth
+ * are different QualifiedIntervals, although both are a tritone.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct QualifiedInterval {
+	pub quality: IntervalQuality,
+	pub unspecified_interval: UnspecifiedInterval,
+}
+
+impl QualifiedInterval {
+	/*
+	 * Returns None, if the quality does not fit the interval (like a perfect third or a major fifth).
+	 */
+	pub fn new(quality: IntervalQuality, unspecified_interval: UnspecifiedInterval) -> Option<QualifiedInterval> {
+		let is_perfect = unspecified_interval.is_perfect();
+		match quality {
+			IntervalQuality::Perfect if !is_perfect => None,
+			IntervalQuality::Major | IntervalQuality::Minor if is_perfect => None,
+			IntervalQuality::Augmented(0) | IntervalQuality::Diminished(0) => None,
+			_ => Some(QualifiedInterval { quality, unspecified_interval }),
+		}
+	}
+
+	/*
+	 * Returns the quality of the interval with the given number of halftones spanning the given diatonic steps.
+	 * Returns None, if the interval is more than six halftones away from the major or perfect interval.
+	 */
+	pub fn from_interval_and_steps(interval: Interval, unspecified_interval: UnspecifiedInterval) -> Option<QualifiedInterval> {
+		let offset = interval.halftones - unspecified_interval.to_major_or_perfect().halftones;
+		QualifiedInterval::from_offset(offset, unspecified_interval)
+	}
+
+	/*
+	 * Returns the interval from the lower key up to the upper key, which is at most an octave.
+	 * For example the interval from c up to f-sharp is an augmented fourth and from c up to g-flat a diminished fifth.
+	 * Returns None, if the interval is more than six halftones away from the major or perfect interval,
+	 * like from c-quadruple-flat up to g-quadruple-sharp.
+	 */
+	pub fn from_spelled_keys(lower: SpelledKey, upper: SpelledKey) -> Option<QualifiedInterval> {
+		let steps = neg_modulo(upper.letter.get_index() as i32 - lower.letter.get_index() as i32, UnspecifiedInterval::octave().value as u32) as i32;
+		let unspecified_interval = UnspecifiedInterval::new(steps);
+		let halftones = Key::to_interval(lower.letter.to_key(), upper.letter.to_key()).halftones
+			+ upper.accidentals as i32 - lower.accidentals as i32;
+		let offset = halftones - unspecified_interval.to_major_or_perfect().halftones;
+		QualifiedInterval::from_offset(offset, unspecified_interval)
+	}
+
+	fn from_offset(offset: i32, unspecified_interval: UnspecifiedInterval) -> Option<QualifiedInterval> {
+		if offset.abs() > 6 {
+			return None;
+		}
+		let quality = match (unspecified_interval.is_perfect(), offset) {
+			(true, 0) => IntervalQuality::Perfect,
+			(false, 0) => IntervalQuality::Major,
+			(false, -1) => IntervalQuality::Minor,
+			(_, o) if o > 0 => IntervalQuality::Augmented(o as u8),
+			(true, o) => IntervalQuality::Diminished(-o as u8),
+			(false, o) => IntervalQuality::Diminished((-o - 1) as u8),
+		};
+		Some(QualifiedInterval { quality, unspecified_interval })
+	}
+
+	pub fn to_interval(self) -> Interval {
+		let mut interval = self.unspecified_interval.to_major_or_perfect();
+		interval.halftones += match (self.quality, self.unspecified_interval.is_perfect()) {
+			(IntervalQuality::Perfect, _) | (IntervalQuality::Major, _) => 0,
+			(IntervalQuality::Minor, _) => -1,
+			(IntervalQuality::Augmented(n), _) => n as i32,
+			(IntervalQuality::Diminished(n), true) => -(n as i32),
+			(IntervalQuality::Diminished(n), false) => -(n as i32) - 1,
+		};
+		interval
+	}
+
+	/*
+	 * Returns the complementary interval, which adds up to an octave with the base interval of this interval.
+	 * Major becomes minor, augmented becomes diminished and the other way around.
+	 */
+	pub fn inverted(self) -> QualifiedInterval {
+		let base = self.unspecified_interval.to_base_unspecified_interval();
+		let inverted_value = if base.value == 0 && self.unspecified_interval.value != 0 {
+			0
+		} else {
+			UnspecifiedInterval::octave().value - base.value
+		};
+		let quality = match self.quality {
+			IntervalQuality::Perfect => IntervalQuality::Perfect,
+			IntervalQuality::Major => IntervalQuality::Minor,
+			IntervalQuality::Minor => IntervalQuality::Major,
+			IntervalQuality::Augmented(n) => IntervalQuality::Diminished(n),
+			IntervalQuality::Diminished(n) => IntervalQuality::Augmented(n),
+		};
+		QualifiedInterval {
+			quality,
+			unspecified_interval: UnspecifiedInterval::new(inverted_value),
+		}
+	}
+}
+
+impl From<QualifiedInterval> for Interval {
+	fn from(qualified_interval: QualifiedInterval) -> Interval {
+		qualified_interval.to_interval()
+	}
+}
+
+impl fmt::Display for IntervalQuality {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			IntervalQuality::Perfect => write!(f, "P"),
+			IntervalQuality::Major => write!(f, "M"),
+			IntervalQuality::Minor => write!(f, "m"),
+			IntervalQuality::Augmented(n) => write!(f, "{}", "A".repeat(*n as usize)),
+			IntervalQuality::Diminished(n) => write!(f, "{}", "d".repeat(*n as usize)),
+		}
+	}
+}
+
+/*
+ * Writes the interval in the short notation like "m3", "P5" or "A4".
+ */
+impl fmt::Display for QualifiedInterval {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}{}", self.quality, self.unspecified_interval.value + 1)
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::spelled_key::Letter;
 
 	#[test]
 	pub fn test_to_major_or_perfect() {
@@ -167,4 +338,50 @@ mod tests {
 		assert_eq!(UnspecifiedInterval::new(-1).to_minor_or_perfect(), Interval::minor_seventh());
 		assert_eq!(UnspecifiedInterval::tenth().to_minor_or_perfect(), Interval::minor_tenth());
 	}
+
+	#[test]
+	pub fn qualified_interval_to_interval() {
+		let augmented_fourth = QualifiedInterval::new(IntervalQuality::Augmented(1), UnspecifiedInterval::fourth()).unwrap();
+		let diminished_fifth = QualifiedInterval::new(IntervalQuality::Diminished(1), UnspecifiedInterval::fifth()).unwrap();
+		assert_ne!(augmented_fourth, diminished_fifth);
+		assert_eq!(augmented_fourth.to_interval(), Interval::tritone());
+		assert_eq!(diminished_fifth.to_interval(), Interval::tritone());
+
+		let diminished_seventh = QualifiedInterval::new(IntervalQuality::Diminished(1), UnspecifiedInterval::seventh()).unwrap();
+		assert_eq!(diminished_seventh.to_interval(), Interval::major_sixth());
+		let doubly_augmented_fourth = QualifiedInterval::new(IntervalQuality::Augmented(2), UnspecifiedInterval::fourth()).unwrap();
+		assert_eq!(doubly_augmented_fourth.to_interval(), Interval::fifth());
+		let minor_tenth = QualifiedInterval::new(IntervalQuality::Minor, UnspecifiedInterval::tenth()).unwrap();
+		assert_eq!(minor_tenth.to_interval(), Interval::minor_tenth());
+
+		assert_eq!(QualifiedInterval::new(IntervalQuality::Perfect, UnspecifiedInterval::third()), None);
+		assert_eq!(QualifiedInterval::new(IntervalQuality::Major, UnspecifiedInterval::fifth()), None);
+	}
+
+	#[test]
+	pub fn qualified_interval_from_spelled_keys() {
+		let c = SpelledKey::natural(Letter::C);
+		let interval = QualifiedInterval::from_spelled_keys(c, SpelledKey::new(Letter::F, 1)).unwrap();
+		assert_eq!(interval.to_string(), "A4");
+		let interval = QualifiedInterval::from_spelled_keys(c, SpelledKey::new(Letter::G, -1)).unwrap();
+		assert_eq!(interval.to_string(), "d5");
+		let interval = QualifiedInterval::from_spelled_keys(SpelledKey::new(Letter::E, -1), SpelledKey::new(Letter::C, 2)).unwrap();
+		assert_eq!(interval.to_string(), "AA6");
+		let interval = QualifiedInterval::from_spelled_keys(SpelledKey::new(Letter::B, 0), SpelledKey::new(Letter::A, 1)).unwrap();
+		assert_eq!(interval.to_string(), "M7");
+		let interval = QualifiedInterval::from_spelled_keys(c, SpelledKey::new(Letter::C, -1)).unwrap();
+		assert_eq!(interval.to_string(), "d1");
+		assert_eq!(QualifiedInterval::from_spelled_keys(SpelledKey::new(Letter::C, -4), SpelledKey::new(Letter::G, 4)), None);
+		let interval = QualifiedInterval::from_interval_and_steps(Interval::augmented_eleventh(), UnspecifiedInterval::eleventh()).unwrap();
+		assert_eq!(interval.to_string(), "A11");
+	}
+
+	#[test]
+	pub fn qualified_interval_inversion() {
+		let inversions = [("M3", "m6"), ("A4", "d5"), ("P1", "P8"), ("P8", "P1"), ("P5", "P4"), ("dd7", "AA2"), ("m10", "M6")];
+		for (interval, inverted) in inversions.iter() {
+			let interval: QualifiedInterval = interval.parse().unwrap();
+			assert_eq!(interval.inverted().to_string(), *inverted);
+		}
+	}
 }
@@ -0,0 +1,302 @@
+use std::fmt;
+
+use crate::keys::Key;
+use crate::spelled_key::{Letter, SpelledKey};
+use crate::scale::{KeyScale, ScaleTemplate};
+use crate::math::neg_modulo;
+
+const NUM_LETTERS: i32 = 7;
+const NUM_HALF_TONES: i32 = 12;
+
+/*
+ * The diatonic modes, which can be written with a key signature.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mode {
+	Major,
+	Minor,
+	Dorian,
+	Phrygian,
+	Lydian,
+	Mixolydian,
+	Locrian,
+}
+
+/*
+ * A key signature with its tonic and mode.
+ * accidentals is the number of sharps (positive) or flats (negative), so it is the position of the
+ * key signature on the circle of fifths. It lies in -7..=7, key signatures with more accidentals are
+ * replaced by their enharmonic equivalent (g-sharp major becomes a-flat major).
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeySignature {
+	pub accidentals: i8,
+	pub tonic: SpelledKey,
+	pub mode: Mode,
+}
+
+impl Mode {
+	/*
+	 * The number of fifths the tonic of this mode lies above the tonic of the major scale with the same keys.
+	 */
+	fn fifths_above_major(self) -> i32 {
+		match self {
+			Mode::Lydian => -1,
+			Mode::Major => 0,
+			Mode::Mixolydian => 1,
+			Mode::Dorian => 2,
+			Mode::Minor => 3,
+			Mode::Phrygian => 4,
+			Mode::Locrian => 5,
+		}
+	}
+
+	pub fn to_scale_template(self) -> ScaleTemplate {
+		match self {
+			Mode::Major => ScaleTemplate::major(),
+			Mode::Minor => ScaleTemplate::minor(),
+			Mode::Dorian => ScaleTemplate::dorian(),
+			Mode::Phrygian => ScaleTemplate::phrygian(),
+			Mode::Lydian => ScaleTemplate::lydian(),
+			Mode::Mixolydian => ScaleTemplate::mixolydian(),
+			Mode::Locrian => ScaleTemplate::locrian(),
+		}
+	}
+}
+
+/*
+ * Returns the position of the given key on the circle of fifths, with c at 0, g at 1 and f at -1.
+ */
+fn fifths_position(spelled_key: SpelledKey) -> i32 {
+	let letter_position = match spelled_key.letter {
+		Letter::F => -1,
+		Letter::C => 0,
+		Letter::G => 1,
+		Letter::D => 2,
+		Letter::A => 3,
+		Letter::E => 4,
+		Letter::B => 5,
+	};
+	letter_position + NUM_LETTERS * spelled_key.accidentals as i32
+}
+
+const MAX_ACCIDENTALS: i32 = 7;
+
+/*
+ * Returns the enharmonic position on the circle of fifths with at most seven sharps or flats.
+ */
+fn normalize_accidentals(accidentals: i32) -> i32 {
+	let mut accidentals = accidentals;
+	while accidentals > MAX_ACCIDENTALS {
+		accidentals -= NUM_HALF_TONES;
+	}
+	while accidentals < -MAX_ACCIDENTALS {
+		accidentals += NUM_HALF_TONES;
+	}
+	accidentals
+}
+
+/*
+ * The inverse of fifths_position.
+ */
+fn from_fifths_position(position: i32) -> SpelledKey {
+	let letters = [Letter::F, Letter::C, Letter::G, Letter::D, Letter::A, Letter::E, Letter::B];
+	let index = neg_modulo(position + 1, NUM_LETTERS as u32) as i32;
+	let accidentals = (position + 1 - index) / NUM_LETTERS;
+	SpelledKey::new(letters[index as usize], accidentals as i8)
+}
+
+impl KeySignature {
+	/*
+	 * Returns the key signature of the tonic, or of its enharmonic equivalent, if the tonic needs more
+	 * than seven sharps or flats.
+	 */
+	pub fn new(tonic: SpelledKey, mode: Mode) -> KeySignature {
+		let accidentals = fifths_position(tonic) - mode.fifths_above_major();
+		if accidentals.abs() > MAX_ACCIDENTALS {
+			return KeySignature::from_fifths(accidentals, mode);
+		}
+		KeySignature { accidentals: accidentals as i8, tonic, mode }
+	}
+
+	pub fn from_accidentals(accidentals: i8, mode: Mode) -> KeySignature {
+		KeySignature::from_fifths(accidentals as i32, mode)
+	}
+
+	fn from_fifths(accidentals: i32, mode: Mode) -> KeySignature {
+		let accidentals = normalize_accidentals(accidentals);
+		let tonic = from_fifths_position(accidentals + mode.fifths_above_major());
+		KeySignature { accidentals: accidentals as i8, tonic, mode }
+	}
+
+	/*
+	 * Returns the key signature of a diatonic mode, of a harmonic minor or of a melodic minor scale.
+	 * Harmonic and melodic minor scales get the key signature of the natural minor scale.
+	 * Returns None for all other scales.
+	 */
+	pub fn from_key_scale(key_scale: &KeyScale) -> Option<KeySignature> {
+		let scale_template = key_scale.to_scale_template();
+		let modes = [Mode::Major, Mode::Minor, Mode::Dorian, Mode::Phrygian, Mode::Lydian, Mode::Mixolydian, Mode::Locrian];
+		let mode = if scale_template == ScaleTemplate::minor_harmonic() || scale_template == ScaleTemplate::minor_melodic() {
+			Mode::Minor
+		} else {
+			*modes.iter().find(|mode| mode.to_scale_template() == scale_template)?
+		};
+
+		// choose the spelling of the tonic with the fewest accidentals, preferring the usual spelling
+		let keynote = key_scale.keynote();
+		[SpelledKey::from_key(keynote), SpelledKey::from_key_with_sharps(keynote), SpelledKey::from_key_with_flats(keynote)].iter()
+			.map(|tonic| KeySignature::new(*tonic, mode))
+			.min_by_key(|key_signature| key_signature.accidentals.abs())
+	}
+
+	pub fn to_key_scale(self) -> KeyScale {
+		KeyScale::from_scale_template(&self.mode.to_scale_template(), self.tonic.to_key())
+	}
+
+	/*
+	 * Returns the keys, which are altered by this key signature, in the order they are written.
+	 */
+	pub fn altered_keys(self) -> Vec<SpelledKey> {
+		if self.accidentals >= 0 {
+			// sharps are written from f-sharp (position 6) upwards
+			(0..self.accidentals as i32).map(|index| from_fifths_position(6 + index)).collect()
+		} else {
+			// flats are written from b-flat (position -2) downwards
+			(0..-self.accidentals as i32).map(|index| from_fifths_position(-2 - index)).collect()
+		}
+	}
+
+	/*
+	 * The neighbor on the circle of fifths with one more sharp or one less flat. The dominant of seven
+	 * sharps is written with four flats.
+	 */
+	pub fn dominant(self) -> KeySignature {
+		KeySignature::from_fifths(self.accidentals as i32 + 1, self.mode)
+	}
+
+	/*
+	 * The neighbor on the circle of fifths with one less sharp or one more flat.
+	 */
+	pub fn subdominant(self) -> KeySignature {
+		KeySignature::from_fifths(self.accidentals as i32 - 1, self.mode)
+	}
+
+	/*
+	 * Returns the major key signature with the same accidentals for minor keys and the minor key signature
+	 * with the same accidentals for all other modes.
+	 */
+	pub fn relative(self) -> KeySignature {
+		let mode = if self.mode == Mode::Minor { Mode::Major } else { Mode::Minor };
+		KeySignature::from_accidentals(self.accidentals, mode)
+	}
+
+	/*
+	 * Returns the number of steps between the two key signatures on the circle of fifths. Enharmonic key
+	 * signatures (like six sharps and six flats) have a distance of 0, so the distance is at most 6.
+	 * Relative keys also have a distance of 0.
+	 */
+	pub fn distance(self, other: KeySignature) -> u8 {
+		let difference = neg_modulo(self.accidentals as i32 - other.accidentals as i32, NUM_HALF_TONES as u32) as i32;
+		difference.min(NUM_HALF_TONES - difference) as u8
+	}
+
+	/*
+	 * Returns the key signature with the given tonic and mode, spelled with the fewest accidentals.
+	 */
+	pub fn from_key_and_mode(tonic: Key, mode: Mode) -> KeySignature {
+		KeySignature::from_key_scale(&KeyScale::from_scale_template(&mode.to_scale_template(), tonic)).unwrap()
+	}
+}
+
+impl fmt::Display for Mode {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Mode::Major => write!(f, "major"),
+			Mode::Minor => write!(f, "minor"),
+			Mode::Dorian => write!(f, "dorian"),
+			Mode::Phrygian => write!(f, "phrygian"),
+			Mode::Lydian => write!(f, "lydian"),
+			Mode::Mixolydian => write!(f, "mixolydian"),
+			Mode::Locrian => write!(f, "locrian"),
+		}
+	}
+}
+
+impl fmt::Display for KeySignature {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{} {}", self.tonic, self.mode)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn key_signature_accidentals() {
+		assert_eq!(KeySignature::new(SpelledKey::natural(Letter::C), Mode::Major).accidentals, 0);
+		assert_eq!(KeySignature::new(SpelledKey::new(Letter::B, -1), Mode::Major).accidentals, -2);
+		assert_eq!(KeySignature::new(SpelledKey::new(Letter::E, -1), Mode::Minor).accidentals, -6);
+		assert_eq!(KeySignature::new(SpelledKey::new(Letter::F, 1), Mode::Minor).accidentals, 3);
+		assert_eq!(KeySignature::new(SpelledKey::natural(Letter::D), Mode::Dorian).accidentals, 0);
+		assert_eq!(KeySignature::from_accidentals(-3, Mode::Major).to_string(), "eb major");
+		assert_eq!(KeySignature::from_accidentals(7, Mode::Minor).to_string(), "a# minor");
+		assert_eq!(KeySignature::from_accidentals(1, Mode::Lydian).to_string(), "c lydian");
+	}
+
+	#[test]
+	pub fn key_signature_from_key_scale() {
+		let major_b_flat_scale = KeyScale::from_scale_template(&ScaleTemplate::major(), Key::ais());
+		assert_eq!(KeySignature::from_key_scale(&major_b_flat_scale).unwrap().to_string(), "bb major");
+		let minor_harmonic_gis_scale = KeyScale::from_scale_template(&ScaleTemplate::minor_harmonic(), Key::gis());
+		assert_eq!(KeySignature::from_key_scale(&minor_harmonic_gis_scale).unwrap().accidentals, 5);
+		let minor_dis_scale = KeyScale::from_scale_template(&ScaleTemplate::minor(), Key::dis());
+		assert_eq!(KeySignature::from_key_scale(&minor_dis_scale).unwrap().to_string(), "eb minor");
+		let blues_scale = KeyScale::from_scale_template(&ScaleTemplate::blues(), Key::c());
+		assert_eq!(KeySignature::from_key_scale(&blues_scale), None);
+		assert_eq!(KeySignature::from_key_and_mode(Key::fis(), Mode::Major).accidentals, 6);
+		assert_eq!(KeySignature::from_accidentals(-4, Mode::Minor).to_key_scale(), KeyScale::from_scale_template(&ScaleTemplate::minor(), Key::f()));
+	}
+
+	#[test]
+	pub fn key_signature_altered_keys() {
+		let names: Vec<String> = KeySignature::from_accidentals(3, Mode::Major).altered_keys().iter().map(|key| key.to_string()).collect();
+		assert_eq!(names, vec!["f#", "c#", "g#"]);
+		let names: Vec<String> = KeySignature::from_accidentals(-4, Mode::Major).altered_keys().iter().map(|key| key.to_string()).collect();
+		assert_eq!(names, vec!["bb", "eb", "ab", "db"]);
+	}
+
+	#[test]
+	pub fn circle_of_fifths() {
+		let c_major = KeySignature::from_accidentals(0, Mode::Major);
+		assert_eq!(c_major.dominant().to_string(), "g major");
+		assert_eq!(c_major.subdominant().to_string(), "f major");
+		assert_eq!(c_major.relative().to_string(), "a minor");
+		assert_eq!(c_major.relative().relative(), c_major);
+
+		assert_eq!(c_major.distance(KeySignature::from_accidentals(2, Mode::Major)), 2);
+		assert_eq!(c_major.distance(KeySignature::from_accidentals(-5, Mode::Minor)), 5);
+		assert_eq!(c_major.distance(c_major.relative()), 0);
+		assert_eq!(KeySignature::from_accidentals(6, Mode::Major).distance(KeySignature::from_accidentals(-6, Mode::Major)), 0);
+		assert_eq!(KeySignature::from_accidentals(5, Mode::Major).distance(KeySignature::from_accidentals(-5, Mode::Major)), 2);
+
+		// at most seven sharps or flats
+		let c_sharp_major = KeySignature::from_accidentals(7, Mode::Major);
+		assert_eq!(c_sharp_major.dominant().to_string(), "ab major");
+		assert_eq!(c_sharp_major.dominant().subdominant().to_string(), "db major");
+		assert_eq!(KeySignature::from_accidentals(-7, Mode::Minor).subdominant().to_string(), "c# minor");
+		assert_eq!(KeySignature::from_accidentals(i8::MAX, Mode::Major).dominant().accidentals, -4);
+	}
+
+	#[test]
+	pub fn enharmonic_key_signatures() {
+		let g_sharp_major = KeySignature::new(SpelledKey::new(Letter::G, 1), Mode::Major);
+		assert_eq!(g_sharp_major.accidentals, -4);
+		assert_eq!(g_sharp_major.to_string(), "ab major");
+		assert_eq!(KeySignature::new(SpelledKey::new(Letter::F, -1), Mode::Major).to_string(), "e major");
+		assert_eq!(KeySignature::new(SpelledKey::new(Letter::A, 1), Mode::Minor).accidentals, 7);
+		assert_eq!(KeySignature::from_accidentals(14, Mode::Major).accidentals, 2);
+		assert_eq!(KeySignature::from_accidentals(-12, Mode::Major).accidentals, 0);
+	}
+}
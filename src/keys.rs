@@ -64,6 +64,10 @@ impl From<Pitch> for Key {
 	}
 }
 
+/*
+ * A key has no spelling, so it is always written with sharps, like "a#" for b-flat.
+ * Use SpelledKey::from_key or the other SpelledKey constructors to write keys with flats.
+ */
 impl fmt::Display for Key {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match self.value % NUM_HALF_TONES {
@@ -106,4 +110,10 @@ mod tests {
 		assert_eq!(Key::f(), Key::from(Pitch { value: -4 }));
 		assert_eq!(Key::b(), Key::from(Pitch { value: 2 }));
 	}
+
+	#[test]
+	pub fn key_display() {
+		assert_eq!(Key::ais().to_string(), "a#");
+		assert_eq!(Pitch { value: 13 }.to_string(), "a#");
+	}
 }
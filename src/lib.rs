@@ -1,11 +1,17 @@
 extern crate num_rational;
 
 pub mod keys;
+pub mod spelled_key;
 pub mod chords;
 pub mod intervals;
 pub mod pitch;
+pub mod tuning;
 pub mod scale;
+pub mod key_signature;
 pub mod rhythm;
 pub mod writer;
 pub mod note;
+pub mod pitch_class_set;
+pub mod tone_row;
+pub mod parse;
 mod math;
@@ -36,6 +36,54 @@ pub fn greatest_common_divisor(value1: i32, value2: i32) -> i32 {
 	return greatest_common_divisor(value2, value1 % value2);
 }
 
+/*
+ * A small xorshift random number generator, so that generated material can be reproduced from its seed.
+ */
+pub struct Random {
+	state: u64,
+}
+
+impl Random {
+	pub fn new(seed: u64) -> Random {
+		// xorshift gets stuck on 0, so the seed is scrambled first
+		let state = (seed ^ 0x9E37_79B9_7F4A_7C15).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		Random { state: if state == 0 { 1 } else { state } }
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		self.state ^= self.state << 13;
+		self.state ^= self.state >> 7;
+		self.state ^= self.state << 17;
+		self.state
+	}
+
+	/*
+	 * Returns a number in 0..bound. bound has to be positive.
+	 */
+	pub fn next_below(&mut self, bound: u32) -> u32 {
+		(self.next_u64() % bound as u64) as u32
+	}
+
+	/*
+	 * Returns an index into the given weights, where every index is chosen with a probability proportional
+	 * to its weight. Returns None, if all weights are 0.
+	 */
+	pub fn choose_weighted(&mut self, weights: &[u32]) -> Option<usize> {
+		let total: u32 = weights.iter().sum();
+		if total == 0 {
+			return None;
+		}
+		let mut value = self.next_below(total);
+		for (index, weight) in weights.iter().enumerate() {
+			if value < *weight {
+				return Some(index);
+			}
+			value -= weight;
+		}
+		None
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -64,4 +112,16 @@ mod tests {
 		assert_eq!(least_common_multiple_of_vec(&vec!(4, 0)), Err("vec should not contain 0"));
 		assert_eq!(least_common_multiple_of_vec(&vec!(4)), Err("vec should at least contain 2 values"));
 	}
+
+	#[test]
+	pub fn test_random() {
+		let mut random = Random::new(42);
+		let values: Vec<u32> = (0..10).map(|_| random.next_below(6)).collect();
+		let mut other_random = Random::new(42);
+		assert_eq!(values, (0..10).map(|_| other_random.next_below(6)).collect::<Vec<u32>>());
+		assert!(values.iter().all(|value| *value < 6));
+
+		assert_eq!(random.choose_weighted(&[0, 5, 0]), Some(1));
+		assert_eq!(random.choose_weighted(&[0, 0]), None);
+	}
 }
@@ -0,0 +1,310 @@
+use std::str::FromStr;
+use std::fmt;
+use std::error::Error;
+
+use crate::keys::Key;
+use crate::spelled_key::{Letter, SpelledKey};
+use crate::pitch::Pitch;
+use crate::intervals::{Interval, UnspecifiedInterval, QualifiedInterval, IntervalQuality};
+use crate::rhythm::{Duration, MAX_DOTS};
+
+/*
+ * The error returned, if a string could not be parsed into a musiclib type.
+ * Every variant contains the string which could not be parsed, except InvalidScalaFile which
+ * describes the line of the file which could not be parsed.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+	Empty,
+	InvalidNoteName(String),
+	InvalidOctave(String),
+	InvalidInterval(String),
+	InvalidDuration(String),
+	InvalidScalaFile(String),
+	InvalidChordSymbol(String),
+	InvalidRomanNumeral(String),
+	InvalidTransformation(String),
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ParseError::Empty => write!(f, "cannot parse empty string"),
+			ParseError::InvalidNoteName(s) => write!(f, "invalid note name \"{}\"", s),
+			ParseError::InvalidOctave(s) => write!(f, "invalid octave \"{}\"", s),
+			ParseError::InvalidInterval(s) => write!(f, "invalid interval \"{}\"", s),
+			ParseError::InvalidDuration(s) => write!(f, "invalid duration \"{}\"", s),
+			ParseError::InvalidScalaFile(s) => write!(f, "invalid scala file: {}", s),
+			ParseError::InvalidChordSymbol(s) => write!(f, "invalid chord symbol \"{}\"", s),
+			ParseError::InvalidRomanNumeral(s) => write!(f, "invalid roman numeral \"{}\"", s),
+			ParseError::InvalidTransformation(s) => write!(f, "invalid transformation \"{}\"", s),
+		}
+	}
+}
+
+impl Error for ParseError {}
+
+fn parse_letter(c: char) -> Option<Letter> {
+	match c.to_ascii_lowercase() {
+		'a' => Some(Letter::A),
+		'b' => Some(Letter::B),
+		'c' => Some(Letter::C),
+		'd' => Some(Letter::D),
+		'e' => Some(Letter::E),
+		'f' => Some(Letter::F),
+		'g' => Some(Letter::G),
+		_ => None,
+	}
+}
+
+/*
+ * The most sharps or flats a parsed note name can have, so "fx" and "ebb" are the furthest spellings.
+ */
+const MAX_ACCIDENTALS: i8 = 2;
+
+/*
+ * Parses a note name like "C", "f#", "Bb" or "gx" at the beginning of the given string.
+ * Returns the spelled key and the rest of the string.
+ */
+pub(crate) fn parse_note_name(s: &str) -> Result<(SpelledKey, &str), ParseError> {
+	let mut chars = s.char_indices();
+	let letter = match chars.next() {
+		Some((_, c)) => parse_letter(c).ok_or_else(|| ParseError::InvalidNoteName(s.to_string()))?,
+		None => return Err(ParseError::Empty),
+	};
+
+	let mut accidentals: i8 = 0;
+	let mut rest = "";
+	for (index, c) in chars {
+		match c {
+			'#' | '♯' => accidentals += 1,
+			'x' | '𝄪' => accidentals += 2,
+			'b' | '♭' => accidentals -= 1,
+			_ => {
+				rest = &s[index..];
+				break;
+			}
+		}
+		if accidentals.abs() > MAX_ACCIDENTALS {
+			return Err(ParseError::InvalidNoteName(s.to_string()));
+		}
+	}
+	Ok((SpelledKey::new(letter, accidentals), rest))
+}
+
+impl FromStr for SpelledKey {
+	type Err = ParseError;
+
+	/*
+	 * Parses note names like "C", "f#", "Bb", "gx" or "ebb". The letter is case insensitive.
+	 */
+	fn from_str(s: &str) -> Result<SpelledKey, ParseError> {
+		let (spelled_key, rest) = parse_note_name(s.trim())?;
+		if !rest.is_empty() {
+			return Err(ParseError::InvalidNoteName(s.to_string()));
+		}
+		Ok(spelled_key)
+	}
+}
+
+impl FromStr for Key {
+	type Err = ParseError;
+
+	fn from_str(s: &str) -> Result<Key, ParseError> {
+		s.parse::<SpelledKey>().map(|spelled_key| spelled_key.to_key())
+	}
+}
+
+impl FromStr for Pitch {
+	type Err = ParseError;
+
+	/*
+	 * Parses a pitch in scientific pitch notation like "C#4" or "Bb-1".
+	 * C4 is the middle C (MIDI number 60) and octave numbers change between B and C,
+	 * so B#3 is the same pitch as C4.
+	 */
+	fn from_str(s: &str) -> Result<Pitch, ParseError> {
+		let (spelled_key, rest) = parse_note_name(s.trim())?;
+		let invalid_octave = || ParseError::InvalidOctave(s.to_string());
+		let octave: i32 = rest.parse().map_err(|_| invalid_octave())?;
+		let c_key = Letter::C.to_key();
+		let halftones_above_c = Key::to_interval(c_key, spelled_key.letter.to_key()).halftones + spelled_key.accidentals as i32;
+		// Pitch { value: 0 } is MIDI number 21
+		let value = octave.checked_add(1)
+			.and_then(|octave| octave.checked_mul(12))
+			.and_then(|halftones| halftones.checked_add(halftones_above_c - 21))
+			.ok_or_else(invalid_octave)?;
+		Ok(Pitch { value })
+	}
+}
+
+impl FromStr for QualifiedInterval {
+	type Err = ParseError;
+
+	/*
+	 * Parses an interval given by its quality and its number, like "m3", "P5", "A4", "dd7" or "M9".
+	 * The qualities are P (perfect), M (major), m (minor), A (augmented) and d (diminished).
+	 */
+	fn from_str(s: &str) -> Result<QualifiedInterval, ParseError> {
+		let trimmed = s.trim();
+		let invalid = || ParseError::InvalidInterval(s.to_string());
+		if trimmed.is_empty() {
+			return Err(ParseError::Empty);
+		}
+
+		let number_start = trimmed.find(|c: char| c.is_ascii_digit()).ok_or_else(invalid)?;
+		let (quality, number) = trimmed.split_at(number_start);
+		let number: i32 = number.parse().map_err(|_| invalid())?;
+		if number < 1 {
+			return Err(invalid());
+		}
+
+		let quality = match quality {
+			"P" => IntervalQuality::Perfect,
+			"M" => IntervalQuality::Major,
+			"m" => IntervalQuality::Minor,
+			_ if !quality.is_empty() && quality.chars().all(|c| c == 'A') => IntervalQuality::Augmented(quality.len() as u8),
+			_ if !quality.is_empty() && quality.chars().all(|c| c == 'd') => IntervalQuality::Diminished(quality.len() as u8),
+			_ => return Err(invalid()),
+		};
+		QualifiedInterval::new(quality, UnspecifiedInterval::new(number - 1)).ok_or_else(invalid)
+	}
+}
+
+impl FromStr for Interval {
+	type Err = ParseError;
+
+	/*
+	 * Parses an interval like QualifiedInterval does, but forgets about the quality, so "A4" and "d5"
+	 * are the same Interval.
+	 */
+	fn from_str(s: &str) -> Result<Interval, ParseError> {
+		s.parse::<QualifiedInterval>().map(|qualified_interval| qualified_interval.to_interval())
+	}
+}
+
+const MAX_DURATION_TERM: u32 = 1 << 16;
+
+impl FromStr for Duration {
+	type Err = ParseError;
+
+	/*
+	 * Parses a duration given as fraction of a whole note ("1/4", "3/8"), as note value ("4" for a quarter)
+	 * or as letter (w, h, q, e, s for whole, half, quarter, eighth and sixteenth).
+	 * Every following "." adds a dot and a trailing "t" makes it a triplet, so "q." is 3/8 and "8t" is 1/12.
+	 * A duration has at most two dots and the nominator and denominator are at most 65536, so dots and
+	 * triplets can not overflow the fraction.
+	 */
+	fn from_str(s: &str) -> Result<Duration, ParseError> {
+		let trimmed = s.trim();
+		let invalid = || ParseError::InvalidDuration(s.to_string());
+		if trimmed.is_empty() {
+			return Err(ParseError::Empty);
+		}
+
+		let (trimmed, is_triplet) = match trimmed.strip_suffix('t') {
+			Some(rest) => (rest, true),
+			None => (trimmed, false),
+		};
+		let base = trimmed.trim_end_matches('.');
+		let num_dots = (trimmed.len() - base.len()) as u32;
+
+		let (nominator, denominator): (u32, u32) = match base {
+			"w" => (1, 1),
+			"h" => (1, 2),
+			"q" => (1, 4),
+			"e" => (1, 8),
+			"s" => (1, 16),
+			_ => match base.find('/') {
+				Some(index) => {
+					let nominator = base[..index].parse().map_err(|_| invalid())?;
+					let denominator = base[index+1..].parse().map_err(|_| invalid())?;
+					(nominator, denominator)
+				}
+				None => (1, base.parse().map_err(|_| invalid())?),
+			},
+		};
+		if nominator == 0 || denominator == 0 || nominator > MAX_DURATION_TERM || denominator > MAX_DURATION_TERM {
+			return Err(invalid());
+		}
+
+		if num_dots > MAX_DOTS {
+			return Err(invalid());
+		}
+		let duration = Duration::new(nominator, denominator).with_dots(num_dots);
+		Ok(if is_triplet { duration.triplet() } else { duration })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn parse_keys() {
+		assert_eq!("C#".parse::<Key>(), Ok(Key::cis()));
+		assert_eq!("Db".parse::<Key>(), Ok(Key::cis()));
+		assert_eq!("f".parse::<Key>(), Ok(Key::f()));
+		assert_eq!("Bb".parse::<Key>(), Ok(Key::ais()));
+		assert_eq!("gx".parse::<Key>(), Ok(Key::a()));
+		assert_eq!("ebb".parse::<SpelledKey>(), Ok(SpelledKey::new(Letter::E, -2)));
+		assert_eq!("H".parse::<Key>(), Err(ParseError::InvalidNoteName("H".to_string())));
+		assert_eq!("C###".parse::<Key>(), Err(ParseError::InvalidNoteName("C###".to_string())));
+		let many_sharps = format!("C{}", "#".repeat(130));
+		assert_eq!(many_sharps.parse::<Key>(), Err(ParseError::InvalidNoteName(many_sharps.clone())));
+		let many_double_sharps = format!("C{}4", "x".repeat(70));
+		assert_eq!(many_double_sharps.parse::<Pitch>(), Err(ParseError::InvalidNoteName(many_double_sharps.clone())));
+		let many_flats = format!("C{}", "b".repeat(130));
+		assert_eq!(many_flats.parse::<SpelledKey>(), Err(ParseError::InvalidNoteName(many_flats.clone())));
+		assert_eq!("C#m".parse::<Key>(), Err(ParseError::InvalidNoteName("C#m".to_string())));
+		assert_eq!("".parse::<Key>(), Err(ParseError::Empty));
+	}
+
+	#[test]
+	pub fn parse_pitches() {
+		assert_eq!("A0".parse::<Pitch>(), Ok(Pitch { value: 0 }));
+		assert_eq!("C4".parse::<Pitch>().unwrap().to_midi_number(), Some(60));
+		assert_eq!("C#4".parse::<Pitch>().unwrap().to_midi_number(), Some(61));
+		assert_eq!("B#3".parse::<Pitch>(), "C4".parse::<Pitch>());
+		assert_eq!("Cb4".parse::<Pitch>(), "B3".parse::<Pitch>());
+		assert_eq!("Bb-1".parse::<Pitch>(), Ok(Pitch { value: -11 }));
+		assert_eq!("C".parse::<Pitch>(), Err(ParseError::InvalidOctave("C".to_string())));
+		assert_eq!("C999999999".parse::<Pitch>(), Err(ParseError::InvalidOctave("C999999999".to_string())));
+		assert_eq!("C-999999999".parse::<Pitch>(), Err(ParseError::InvalidOctave("C-999999999".to_string())));
+	}
+
+	#[test]
+	pub fn parse_intervals() {
+		assert_eq!("m3".parse::<Interval>(), Ok(Interval::minor_third()));
+		assert_eq!("P5".parse::<Interval>(), Ok(Interval::fifth()));
+		assert_eq!("A4".parse::<Interval>(), Ok(Interval::tritone()));
+		assert_eq!("d5".parse::<Interval>(), Ok(Interval::tritone()));
+		assert_eq!("d7".parse::<Interval>(), Ok(Interval::major_sixth()));
+		assert_eq!("M9".parse::<Interval>(), Ok(Interval::major_ninth()));
+		assert_eq!("P8".parse::<Interval>(), Ok(Interval::octave()));
+		assert_eq!("AA4".parse::<Interval>(), Ok(Interval::fifth()));
+		assert_eq!("P3".parse::<Interval>(), Err(ParseError::InvalidInterval("P3".to_string())));
+		assert_eq!("M0".parse::<Interval>(), Err(ParseError::InvalidInterval("M0".to_string())));
+	}
+
+	#[test]
+	pub fn parse_durations() {
+		assert!("1/4".parse::<Duration>() == Ok(Duration::quarter()));
+		assert!("4".parse::<Duration>() == Ok(Duration::quarter()));
+		assert!("q.".parse::<Duration>() == Ok(Duration::new(3, 8)));
+		assert!("h..".parse::<Duration>() == Ok(Duration::new(7, 8)));
+		assert!("8t".parse::<Duration>() == Ok(Duration::new(1, 12)));
+		assert_eq!("h..".parse::<Duration>().map(|duration| duration.get_dots()), Ok(2));
+		assert!("8t".parse::<Duration>().unwrap().get_base() == Duration::eighth());
+		assert!("3/8".parse::<Duration>() == Ok(Duration::new(3, 8)));
+		assert!("1/0".parse::<Duration>() == Err(ParseError::InvalidDuration("1/0".to_string())));
+		assert!("x".parse::<Duration>() == Err(ParseError::InvalidDuration("x".to_string())));
+		assert!("q...".parse::<Duration>() == Err(ParseError::InvalidDuration("q...".to_string())));
+		for input in ["4294967295/1.", "1/4294967295t", "1/4294967295..", "65537/4", "1/65537"].iter() {
+			assert!(input.parse::<Duration>() == Err(ParseError::InvalidDuration(input.to_string())));
+		}
+		assert!("1/65536..t".parse::<Duration>() == Ok(Duration::new(1, 65536).double_dotted().triplet()));
+		let many_dots = format!("q{}", ".".repeat(30));
+		assert!(many_dots.parse::<Duration>() == Err(ParseError::InvalidDuration(many_dots.clone())));
+	}
+}
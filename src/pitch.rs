@@ -6,7 +6,7 @@ use crate::intervals::Interval;
 
 /*
  * the keyboard is divided into octaves starting with A.
- * value = 0, means a pitch of 27.5Hz or MIDI number 21
+ * value = 0, means a pitch of 27.5Hz (with a concert pitch of 440Hz) or MIDI number 21
  * (see https://newt.phys.unsw.edu.au/jw/notes.html).
  * Negative values for Pitch.value are possible.
  */
@@ -20,6 +20,14 @@ pub struct Octave {
 	pub value: i32,
 }
 
+/*
+ * The frequency of the concert A (A4, Pitch { value: 48 }) in Hz, which all other frequencies are derived from.
+ */
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ConcertPitch {
+	pub frequency: f64,
+}
+
 #[derive(Copy, Clone)]
 pub struct PitchRange {
 	pub lower: Pitch,
@@ -39,6 +47,28 @@ impl Pitch {
 		}
 	}
 
+	/*
+	 * Returns the frequency of this pitch in Hz in twelve-tone equal temperament.
+	 */
+	pub fn to_frequency(self, concert_pitch: ConcertPitch) -> f64 {
+		concert_pitch.frequency * 2f64.powf((self.value - ConcertPitch::PITCH.value) as f64 / 12.0)
+	}
+
+	/*
+	 * Returns the pitch nearest to the given frequency in twelve-tone equal temperament and the deviation
+	 * of the frequency from this pitch in cents (in the range -50 to 50).
+	 * Returns None for frequencies, which are not positive.
+	 */
+	pub fn from_frequency(frequency: f64, concert_pitch: ConcertPitch) -> Option<(Pitch, f64)> {
+		let is_valid = |frequency: f64| frequency.is_normal() && frequency > 0.0;
+		if !is_valid(frequency) || !is_valid(concert_pitch.frequency) {
+			return None;
+		}
+		let halftones = 12.0 * (frequency / concert_pitch.frequency).log2() + ConcertPitch::PITCH.value as f64;
+		let nearest = halftones.round();
+		Some((Pitch { value: nearest as i32 }, (halftones - nearest) * 100.0))
+	}
+
 	pub fn get_upper_interval(self, upper_key: Key) -> Interval {
 		let lower_key: Key = self.into();
 		Key::to_interval(lower_key, upper_key)
@@ -83,6 +113,9 @@ impl SubAssign<Interval> for Pitch {
 	}
 }
 
+/*
+ * Writes the key of the pitch without octave, with sharps like the Display of Key.
+ */
 impl fmt::Display for Pitch {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		let key = Key::from(*self);
@@ -90,6 +123,29 @@ impl fmt::Display for Pitch {
 	}
 }
 
+impl ConcertPitch {
+	/*
+	 * The pitch which sounds with the frequency of the concert pitch.
+	 */
+	pub const PITCH: Pitch = Pitch { value: 48 };
+
+	pub fn new(frequency: f64) -> ConcertPitch {
+		ConcertPitch { frequency }
+	}
+
+	pub fn standard() -> ConcertPitch {
+		ConcertPitch { frequency: 440.0 }
+	}
+
+	pub fn orchestral() -> ConcertPitch {
+		ConcertPitch { frequency: 442.0 }
+	}
+
+	pub fn baroque() -> ConcertPitch {
+		ConcertPitch { frequency: 415.0 }
+	}
+}
+
 impl PitchRange {
 	pub fn from_pitches(pitch1: Pitch, pitch2: Pitch) -> PitchRange {
 		if pitch1 < pitch2 {
@@ -115,3 +171,41 @@ impl PitchRange {
 		pitches
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_close(value: f64, expected: f64) {
+		assert!((value - expected).abs() < 1e-6, "{} != {}", value, expected);
+	}
+
+	#[test]
+	pub fn pitch_to_frequency() {
+		assert_close(Pitch { value: 0 }.to_frequency(ConcertPitch::standard()), 27.5);
+		assert_close(Pitch { value: 48 }.to_frequency(ConcertPitch::standard()), 440.0);
+		assert_close(Pitch { value: 60 }.to_frequency(ConcertPitch::orchestral()), 884.0);
+		assert_close(Pitch { value: 36 }.to_frequency(ConcertPitch::baroque()), 207.5);
+		assert_close(Pitch { value: 51 }.to_frequency(ConcertPitch::standard()), 523.2511306011972);
+	}
+
+	#[test]
+	pub fn pitch_from_frequency() {
+		let (pitch, cents) = Pitch::from_frequency(440.0, ConcertPitch::standard()).unwrap();
+		assert_eq!(pitch, Pitch { value: 48 });
+		assert_close(cents, 0.0);
+
+		let (pitch, cents) = Pitch::from_frequency(442.0, ConcertPitch::standard()).unwrap();
+		assert_eq!(pitch, Pitch { value: 48 });
+		assert_close(cents, 7.851415040126504);
+
+		let (pitch, cents) = Pitch::from_frequency(27.0, ConcertPitch::standard()).unwrap();
+		assert_eq!(pitch, Pitch { value: 0 });
+		assert!(cents < 0.0);
+
+		let (pitch, _) = Pitch::from_frequency(440.0, ConcertPitch::baroque()).unwrap();
+		assert_eq!(pitch, Pitch { value: 49 });
+
+		assert_eq!(Pitch::from_frequency(0.0, ConcertPitch::standard()), None);
+	}
+}
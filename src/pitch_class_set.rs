@@ -0,0 +1,339 @@
+use std::convert::From;
+use std::fmt;
+
+use crate::keys::Key;
+use crate::intervals::Interval;
+use crate::scale::KeyScale;
+use crate::chords::KeyChord;
+
+const NUM_PITCH_CLASSES: u32 = 12;
+const ALL_PITCH_CLASSES: u16 = 0xfff;
+// the key value of c, which is pitch class 0
+const PITCH_CLASS_OFFSET: u8 = 3;
+
+/*
+ * The prime forms (as listed by Rahn) of all set classes up to six pitch classes, ordered by their Forte
+ * numbers. Larger set classes have the ordinal number of their complement.
+ */
+const PRIME_FORMS: [&[&[u8]]; 7] = [
+	&[&[]],
+	&[&[0]],
+	&[&[0, 1], &[0, 2], &[0, 3], &[0, 4], &[0, 5], &[0, 6]],
+	&[
+		&[0, 1, 2], &[0, 1, 3], &[0, 1, 4], &[0, 1, 5], &[0, 1, 6], &[0, 2, 4], &[0, 2, 5], &[0, 2, 6], &[0, 2, 7],
+		&[0, 3, 6], &[0, 3, 7], &[0, 4, 8],
+	],
+	&[
+		&[0, 1, 2, 3], &[0, 1, 2, 4], &[0, 1, 3, 4], &[0, 1, 2, 5], &[0, 1, 2, 6], &[0, 1, 2, 7], &[0, 1, 4, 5],
+		&[0, 1, 5, 6], &[0, 1, 6, 7], &[0, 2, 3, 5], &[0, 1, 3, 5], &[0, 2, 3, 6], &[0, 1, 3, 6], &[0, 2, 3, 7],
+		&[0, 1, 4, 6], &[0, 1, 5, 7], &[0, 3, 4, 7], &[0, 1, 4, 7], &[0, 1, 4, 8], &[0, 1, 5, 8], &[0, 2, 4, 6],
+		&[0, 2, 4, 7], &[0, 2, 5, 7], &[0, 2, 4, 8], &[0, 2, 6, 8], &[0, 3, 5, 8], &[0, 2, 5, 8], &[0, 3, 6, 9],
+		&[0, 1, 3, 7],
+	],
+	&[
+		&[0, 1, 2, 3, 4], &[0, 1, 2, 3, 5], &[0, 1, 2, 4, 5], &[0, 1, 2, 3, 6], &[0, 1, 2, 3, 7], &[0, 1, 2, 5, 6],
+		&[0, 1, 2, 6, 7], &[0, 2, 3, 4, 6], &[0, 1, 2, 4, 6], &[0, 1, 3, 4, 6], &[0, 2, 3, 4, 7], &[0, 1, 3, 5, 6],
+		&[0, 1, 2, 4, 8], &[0, 1, 2, 5, 7], &[0, 1, 2, 6, 8], &[0, 1, 3, 4, 7], &[0, 1, 3, 4, 8], &[0, 1, 4, 5, 7],
+		&[0, 1, 3, 6, 7], &[0, 1, 5, 6, 8], &[0, 1, 4, 5, 8], &[0, 1, 4, 7, 8], &[0, 2, 3, 5, 7], &[0, 1, 3, 5, 7],
+		&[0, 2, 3, 5, 8], &[0, 2, 4, 5, 8], &[0, 1, 3, 5, 8], &[0, 2, 3, 6, 8], &[0, 1, 3, 6, 8], &[0, 1, 4, 6, 8],
+		&[0, 1, 3, 6, 9], &[0, 1, 4, 6, 9], &[0, 2, 4, 6, 8], &[0, 2, 4, 6, 9], &[0, 2, 4, 7, 9], &[0, 1, 2, 4, 7],
+		&[0, 3, 4, 5, 8], &[0, 1, 2, 5, 8],
+	],
+	&[
+		&[0, 1, 2, 3, 4, 5], &[0, 1, 2, 3, 4, 6], &[0, 1, 2, 3, 5, 6], &[0, 1, 2, 4, 5, 6], &[0, 1, 2, 3, 6, 7],
+		&[0, 1, 2, 5, 6, 7], &[0, 1, 2, 6, 7, 8], &[0, 2, 3, 4, 5, 7], &[0, 1, 2, 3, 5, 7], &[0, 1, 3, 4, 5, 7],
+		&[0, 1, 2, 4, 5, 7], &[0, 1, 2, 4, 6, 7], &[0, 1, 3, 4, 6, 7], &[0, 1, 3, 4, 5, 8], &[0, 1, 2, 4, 5, 8],
+		&[0, 1, 4, 5, 6, 8], &[0, 1, 2, 4, 7, 8], &[0, 1, 2, 5, 7, 8], &[0, 1, 3, 4, 7, 8], &[0, 1, 4, 5, 8, 9],
+		&[0, 2, 3, 4, 6, 8], &[0, 1, 2, 4, 6, 8], &[0, 2, 3, 5, 6, 8], &[0, 1, 3, 4, 6, 8], &[0, 1, 3, 5, 6, 8],
+		&[0, 1, 3, 5, 7, 8], &[0, 1, 3, 4, 6, 9], &[0, 1, 3, 5, 6, 9], &[0, 2, 3, 6, 7, 9], &[0, 1, 3, 6, 7, 9],
+		&[0, 1, 4, 5, 7, 9], &[0, 2, 4, 5, 7, 9], &[0, 2, 3, 5, 7, 9], &[0, 1, 3, 5, 7, 9], &[0, 2, 4, 6, 8, 10],
+		&[0, 1, 2, 3, 4, 7], &[0, 1, 2, 3, 4, 8], &[0, 1, 2, 3, 7, 8], &[0, 2, 3, 4, 5, 8], &[0, 1, 2, 3, 5, 8],
+		&[0, 1, 2, 3, 6, 8], &[0, 1, 2, 3, 6, 9], &[0, 1, 2, 5, 6, 8], &[0, 1, 2, 5, 6, 9], &[0, 2, 3, 4, 6, 9],
+		&[0, 1, 2, 4, 6, 9], &[0, 1, 2, 4, 7, 9], &[0, 1, 2, 5, 7, 9], &[0, 1, 3, 4, 7, 9], &[0, 1, 4, 6, 7, 9],
+	],
+];
+
+/*
+ * An unordered set of keys, stored as bitmask: bit n is set, if the key with the value n is part of the set.
+ * As usual in set theory the pitch classes are numbered from c (0) to b (11), see pitch_classes.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PitchClassSet {
+	pub mask: u16,
+}
+
+/*
+ * The name of a set class in Forte's list, like 4-Z15. Z-related set classes share their interval-class
+ * vector with another set class.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ForteNumber {
+	pub cardinality: usize,
+	pub ordinal: usize,
+	pub is_z_related: bool,
+}
+
+fn rotate(mask: u16, halftones: i32) -> u16 {
+	let halftones = halftones.rem_euclid(NUM_PITCH_CLASSES as i32) as u32;
+	((mask << halftones) | (mask >> ((NUM_PITCH_CLASSES - halftones) % NUM_PITCH_CLASSES))) & ALL_PITCH_CLASSES
+}
+
+fn bits(mask: u16) -> Vec<u8> {
+	(0..NUM_PITCH_CLASSES as u8).filter(|bit| mask & 1 << bit != 0).collect()
+}
+
+impl PitchClassSet {
+	pub fn new() -> PitchClassSet {
+		PitchClassSet { mask: 0 }
+	}
+
+	pub fn from_keys(keys: &[Key]) -> PitchClassSet {
+		PitchClassSet { mask: keys.iter().fold(0, |mask, key| mask | 1 << key.get_value()) }
+	}
+
+	/*
+	 * Returns the keys of the set ordered by their pitch classes, starting with c.
+	 */
+	pub fn keys(&self) -> Vec<Key> {
+		self.pitch_classes().iter().map(|pitch_class| Key::new((pitch_class + PITCH_CLASS_OFFSET) % NUM_PITCH_CLASSES as u8)).collect()
+	}
+
+	/*
+	 * Returns the integers of the pitch classes in ascending order, c is 0, c# is 1 and so on.
+	 */
+	pub fn pitch_classes(&self) -> Vec<u8> {
+		bits(self.pitch_class_mask())
+	}
+
+	// the bitmask with c as bit 0
+	fn pitch_class_mask(&self) -> u16 {
+		rotate(self.mask, -(PITCH_CLASS_OFFSET as i32))
+	}
+
+	pub fn contains(&self, key: Key) -> bool {
+		self.mask & 1 << key.get_value() != 0
+	}
+
+	pub fn insert(&mut self, key: Key) {
+		self.mask |= 1 << key.get_value();
+	}
+
+	pub fn len(&self) -> usize {
+		self.mask.count_ones() as usize
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.mask == 0
+	}
+
+	/*
+	 * Tn: moves every key of the set by the interval.
+	 */
+	pub fn transpose(&self, interval: Interval) -> PitchClassSet {
+		PitchClassSet { mask: rotate(self.mask, interval.halftones) }
+	}
+
+	/*
+	 * TnI: inverts the set around c and transposes it by the given number of half tones afterwards, so
+	 * pitch class p becomes n - p.
+	 */
+	pub fn invert(&self, halftones: i32) -> PitchClassSet {
+		let pitch_classes: Vec<u8> = self.pitch_classes().iter()
+			.map(|pitch_class| (halftones - *pitch_class as i32).rem_euclid(NUM_PITCH_CLASSES as i32) as u8)
+			.collect();
+		PitchClassSet::from_pitch_class_integers(&pitch_classes)
+	}
+
+	/*
+	 * Builds a set from pitch class integers, see pitch_classes.
+	 */
+	pub fn from_pitch_class_integers(pitch_classes: &[u8]) -> PitchClassSet {
+		let mask = pitch_classes.iter().fold(0, |mask, pitch_class| mask | 1 << pitch_class);
+		PitchClassSet { mask: rotate(mask, PITCH_CLASS_OFFSET as i32) }
+	}
+
+	pub fn complement(&self) -> PitchClassSet {
+		PitchClassSet { mask: !self.mask & ALL_PITCH_CLASSES }
+	}
+
+	pub fn is_subset_of(&self, other: &PitchClassSet) -> bool {
+		self.mask & other.mask == self.mask
+	}
+
+	pub fn is_superset_of(&self, other: &PitchClassSet) -> bool {
+		other.is_subset_of(self)
+	}
+
+	/*
+	 * Returns the keys of the set in their most compact order: the rotation with the smallest span between
+	 * its first and last key, then with the smallest span between its first and second to last key and so
+	 * on. Symmetric sets start with the lowest pitch class.
+	 */
+	pub fn normal_form(&self) -> Vec<Key> {
+		let pitch_classes = self.pitch_classes();
+		let best_start = (0..pitch_classes.len())
+			.min_by_key(|index| rotate(self.pitch_class_mask(), -(pitch_classes[*index] as i32)))
+			.unwrap_or(0);
+		let keys = self.keys();
+		keys[best_start..].iter().chain(keys[..best_start].iter()).cloned().collect()
+	}
+
+	/*
+	 * Returns the most compact form of the set or its inversion transposed to start with 0, so all sets of
+	 * the same set class have the same prime form. Follows Rahn, who compares the forms like normal_form.
+	 */
+	pub fn prime_form(&self) -> Vec<u8> {
+		let mask = self.pitch_class_mask();
+		let inverted_mask = self.invert(0).pitch_class_mask();
+		let prime_mask = (0..NUM_PITCH_CLASSES as i32)
+			.flat_map(|halftones| vec![rotate(mask, halftones), rotate(inverted_mask, halftones)])
+			.filter(|mask| *mask & 1 != 0 || *mask == 0)
+			.min()
+			.unwrap_or(0);
+		bits(prime_mask)
+	}
+
+	/*
+	 * Counts the intervals between all pairs of keys by their interval class: the first value counts the
+	 * minor seconds and major sevenths, the last one the tritones.
+	 */
+	pub fn interval_class_vector(&self) -> [u8; 6] {
+		let mut vector = [0; 6];
+		let keys = self.keys();
+		for (index, key) in keys.iter().enumerate() {
+			for other_key in keys[index + 1..].iter() {
+				let halftones = Key::to_interval(*key, *other_key).halftones;
+				vector[halftones.min(NUM_PITCH_CLASSES as i32 - halftones) as usize - 1] += 1;
+			}
+		}
+		vector
+	}
+
+	pub fn forte_number(&self) -> ForteNumber {
+		let cardinality = self.len();
+		if cardinality > PRIME_FORMS.len() - 1 {
+			let complement = self.complement().forte_number();
+			return ForteNumber { cardinality, ..complement };
+		}
+		let prime_form = self.prime_form();
+		let prime_forms = PRIME_FORMS[cardinality];
+		let index = prime_forms.iter().position(|other| *other == &prime_form[..]).unwrap();
+		let vector = self.interval_class_vector();
+		let is_z_related = prime_forms.iter().enumerate()
+			.any(|(other_index, other)| other_index != index && PitchClassSet::from_pitch_class_integers(other).interval_class_vector() == vector);
+		ForteNumber { cardinality, ordinal: index + 1, is_z_related }
+	}
+}
+
+impl Default for PitchClassSet {
+	fn default() -> Self {
+		PitchClassSet::new()
+	}
+}
+
+impl From<&KeyChord> for PitchClassSet {
+	fn from(chord: &KeyChord) -> PitchClassSet {
+		PitchClassSet::from_keys(&chord.keys)
+	}
+}
+
+impl From<&KeyScale> for PitchClassSet {
+	fn from(scale: &KeyScale) -> PitchClassSet {
+		PitchClassSet::from_keys(&scale.keys)
+	}
+}
+
+/*
+ * Writes the pitch class integers like {0, 4, 7}.
+ */
+impl fmt::Display for PitchClassSet {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let pitch_classes: Vec<String> = self.pitch_classes().iter().map(|pitch_class| pitch_class.to_string()).collect();
+		write!(f, "{{{}}}", pitch_classes.join(", "))
+	}
+}
+
+impl fmt::Display for ForteNumber {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}-{}{}", self.cardinality, if self.is_z_related { "Z" } else { "" }, self.ordinal)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::HashSet;
+	use crate::chords::ChordTemplate;
+	use crate::scale::ScaleTemplate;
+
+	#[test]
+	pub fn normal_and_prime_form() {
+		let c_major = PitchClassSet::from(&KeyChord::from_chord_template(&ChordTemplate::major(), Key::c()));
+		assert_eq!(c_major.pitch_classes(), vec![0, 4, 7]);
+		assert_eq!(c_major.to_string(), "{0, 4, 7}");
+		assert_eq!(c_major.normal_form(), vec![Key::c(), Key::e(), Key::g()]);
+		assert_eq!(c_major.prime_form(), vec![0, 3, 7]);
+
+		let b_major = PitchClassSet::from_keys(&[Key::b(), Key::dis(), Key::fis()]);
+		assert_eq!(b_major.normal_form(), vec![Key::b(), Key::dis(), Key::fis()]);
+		let a_minor = PitchClassSet::from_keys(&[Key::a(), Key::c(), Key::e()]);
+		assert_eq!(a_minor.prime_form(), c_major.prime_form());
+
+		// the tie between e g# c and the other rotations is broken by the lowest pitch class
+		let augmented = PitchClassSet::from_keys(&[Key::e(), Key::gis(), Key::c()]);
+		assert_eq!(augmented.normal_form(), vec![Key::c(), Key::e(), Key::gis()]);
+
+		// 5-20 differs between Forte and Rahn
+		assert_eq!(PitchClassSet::from_pitch_class_integers(&[0, 1, 3, 7, 8]).prime_form(), vec![0, 1, 5, 6, 8]);
+		assert_eq!(PitchClassSet::new().prime_form(), Vec::<u8>::new());
+	}
+
+	#[test]
+	pub fn transposition_and_inversion() {
+		let set = PitchClassSet::from_pitch_class_integers(&[0, 1, 4]);
+		assert_eq!(set.transpose(Interval::major_second()).pitch_classes(), vec![2, 3, 6]);
+		assert_eq!(set.transpose(Interval::major_seventh()).pitch_classes(), vec![0, 3, 11]);
+		assert_eq!(set.invert(0).pitch_classes(), vec![0, 8, 11]);
+		assert_eq!(set.invert(4).pitch_classes(), vec![0, 3, 4]);
+		assert_eq!(set.complement().len(), 9);
+		assert!(set.complement().complement() == set);
+	}
+
+	#[test]
+	pub fn subsets_and_scales() {
+		let c_major_scale = PitchClassSet::from(&KeyScale::from_scale_template(&ScaleTemplate::major(), Key::c()));
+		let g_seventh = PitchClassSet::from(&KeyChord::from_chord_template(&ChordTemplate::dominant_seventh(), Key::g()));
+		assert!(g_seventh.is_subset_of(&c_major_scale));
+		assert!(c_major_scale.is_superset_of(&g_seventh));
+		assert!(!c_major_scale.is_subset_of(&g_seventh));
+		assert!(c_major_scale.contains(Key::b()));
+		assert!(!c_major_scale.contains(Key::ais()));
+
+		assert_eq!(c_major_scale.interval_class_vector(), [2, 5, 4, 3, 6, 1]);
+		assert_eq!(c_major_scale.forte_number().to_string(), "7-35");
+		assert_eq!(c_major_scale.complement().forte_number().to_string(), "5-35");
+	}
+
+	#[test]
+	pub fn forte_numbers() {
+		assert_eq!(PitchClassSet::from_pitch_class_integers(&[0, 4, 7]).forte_number().to_string(), "3-11");
+		assert_eq!(PitchClassSet::from_pitch_class_integers(&[0, 1, 4, 6]).forte_number().to_string(), "4-Z15");
+		assert_eq!(PitchClassSet::from_pitch_class_integers(&[0, 1, 3, 7]).forte_number().to_string(), "4-Z29");
+		assert_eq!(PitchClassSet::from_pitch_class_integers(&[0, 2, 4, 6, 8, 10]).forte_number().to_string(), "6-35");
+		assert_eq!(PitchClassSet::from_pitch_class_integers(&[0, 1, 2, 3, 4, 5, 6, 7, 8]).forte_number().to_string(), "9-1");
+
+		// every set class is listed exactly once
+		let prime_forms: HashSet<Vec<u8>> = (0..=ALL_PITCH_CLASSES).map(|mask| PitchClassSet { mask }.prime_form()).collect();
+		assert_eq!(prime_forms.len(), 224);
+		for prime_forms in PRIME_FORMS.iter() {
+			for prime_form in prime_forms.iter() {
+				assert_eq!(&PitchClassSet::from_pitch_class_integers(prime_form).prime_form()[..], *prime_form);
+			}
+		}
+
+		// z-related hexachords are each other's complement
+		let hexachord = PitchClassSet::from_pitch_class_integers(&[0, 1, 2, 3, 5, 6]);
+		assert_eq!(hexachord.forte_number().to_string(), "6-Z3");
+		assert_eq!(hexachord.complement().forte_number().to_string(), "6-Z36");
+	}
+}
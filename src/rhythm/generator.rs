@@ -1,5 +1,12 @@
 use super::{Duration, TimePoint, RhythmNote, BarTimeSignature};
 
+/*
+ * Half of an undotted beat, like an eighth for a quarter beat.
+ */
+fn half_beat(beat: Duration) -> Duration {
+	Duration::from_rational(beat.duration / 2)
+}
+
 pub struct StraitRhythmNotes {
 	time_point: TimePoint,
 	duration: Duration,
@@ -40,12 +47,41 @@ impl RhythmPattern {
 
 		RhythmPattern { notes }
 	}
+
+	/*
+	 * Fills the bar with triplets, so every (undotted) beat is split into three notes of half the beat.
+	 */
+	pub fn triplet_rhythm_notes(bar_time_signature: BarTimeSignature, beat: Duration) -> RhythmPattern {
+		RhythmPattern::straight_rhythm_notes(bar_time_signature, half_beat(beat).triplet())
+	}
+
+	/*
+	 * Fills the bar with swung pairs of notes: every (undotted) beat is split into a long note of two thirds
+	 * of the beat and a short note of one third, notated as triplets of the beat and of half the beat.
+	 */
+	pub fn swing_rhythm_notes(bar_time_signature: BarTimeSignature, beat: Duration) -> RhythmPattern {
+		let long = beat.triplet();
+		let short = half_beat(beat).triplet();
+		let end = TimePoint::from(bar_time_signature);
+
+		let mut counter = TimePoint::new(0, 1);
+		let mut notes: Vec<RhythmNote> = Vec::new();
+		while counter < end {
+			notes.push(RhythmNote::new(counter, long));
+			counter += long;
+			notes.push(RhythmNote::new(counter, short));
+			counter += short;
+		}
+
+		RhythmPattern { notes }
+	}
 }
 
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::rhythm::Tuplet;
 
 	#[test]
 	pub fn test_straight_rhythm_notes_pattern() {
@@ -67,6 +103,26 @@ mod tests {
 	}
 
 
+	#[test]
+	pub fn test_swing_and_triplet_patterns() {
+		let swing = RhythmPattern::swing_rhythm_notes(BarTimeSignature::two_quarter_time(), Duration::quarter());
+		let asserted_swing: Vec<RhythmNote> = vec![
+			RhythmNote::new(TimePoint::new(0, 1), Duration::new(1, 6)),
+			RhythmNote::new(TimePoint::new(1, 6), Duration::new(1, 12)),
+			RhythmNote::new(TimePoint::new(1, 4), Duration::new(1, 6)),
+			RhythmNote::new(TimePoint::new(5, 12), Duration::new(1, 12)),
+		];
+		assert_eq!(swing.notes, asserted_swing);
+		let short = swing.notes[1].get_duration();
+		assert!(short.get_base() == Duration::eighth());
+		assert_eq!(short.get_tuplet(), Some(Tuplet::triplet()));
+
+		let triplets = RhythmPattern::triplet_rhythm_notes(BarTimeSignature::three_quarter_time(), Duration::quarter());
+		assert_eq!(triplets.notes.len(), 9);
+		assert!(triplets.notes.iter().all(|note| note.get_duration().get_tuplet() == Some(Tuplet::triplet())));
+		assert!(triplets.notes[8].get_time_points().1 == TimePoint::new(3, 4));
+	}
+
 	#[test]
 	pub fn test_straight_rhythm_notes_gen() {
 		let duration = Duration::new(1, 4);
@@ -1,6 +1,7 @@
 pub mod generator;
 
 use std::ops::{Add,Sub,AddAssign};
+use std::cmp::Ordering;
 use num_rational::Ratio;
 use std::fmt;
 use std::convert::From;
@@ -9,34 +10,220 @@ use crate::math::least_common_multiple;
 
 type Rational = Ratio<u32>;
 
-#[derive(Clone, Copy, PartialEq, PartialOrd)]
+/*
+ * A tuplet plays actual notes in the time of normal notes of the same note value, so a triplet (3:2)
+ * plays three eighths in the time of two.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Tuplet {
+	pub actual: u32,
+	pub normal: u32,
+}
+
+impl Tuplet {
+	pub fn new(actual: u32, normal: u32) -> Tuplet {
+		Tuplet { actual, normal }
+	}
+
+	pub fn triplet() -> Tuplet {
+		Tuplet { actual: 3, normal: 2 }
+	}
+
+	pub fn quintuplet() -> Tuplet {
+		Tuplet { actual: 5, normal: 4 }
+	}
+
+	fn factor(self) -> Rational {
+		Rational::new(self.normal, self.actual)
+	}
+}
+
+/*
+ * The length of a note as fraction of a whole note. A duration remembers how it is notated: the number
+ * of dots and the tuplet it is part of. Durations are compared by their length only, so a dotted quarter
+ * equals 3/8.
+ */
+#[derive(Clone, Copy)]
 pub struct Duration {
 	duration: Rational,
+	dots: u32,
+	tuplet: Option<Tuplet>,
+}
+
+/*
+ * The most dots a single note can have.
+ */
+pub const MAX_DOTS: u32 = 2;
+
+/*
+ * n dots multiply the duration by (2^(n+1) - 1) / 2^n.
+ */
+fn dot_factor(dots: u32) -> Rational {
+	Rational::new(2u32.pow(dots + 1) - 1, 2u32.pow(dots))
 }
 
 impl Duration {
 	pub fn whole() -> Duration {
-		Duration { duration: Rational::new(1, 1) }
+		Duration::new(1, 1)
 	}
 
 	pub fn half() -> Duration {
-		Duration { duration: Rational::new(1, 2) }
+		Duration::new(1, 2)
 	}
 
 	pub fn quarter() -> Duration {
-		Duration { duration: Rational::new(1, 4) }
+		Duration::new(1, 4)
 	}
 
 	pub fn eighth() -> Duration {
-		Duration { duration: Rational::new(1, 8) }
+		Duration::new(1, 8)
 	}
 
 	pub fn sixteenth() -> Duration {
-		Duration { duration: Rational::new(1, 16) }
+		Duration::new(1, 16)
 	}
 
 	pub fn new(nominator: u32, denominator: u32) -> Duration {
-		Duration { duration: Rational::new(nominator, denominator) }
+		Duration::from_rational(Rational::new(nominator, denominator))
+	}
+
+	fn from_rational(duration: Rational) -> Duration {
+		Duration { duration, dots: 0, tuplet: None }
+	}
+
+	/*
+	 * The written note value without dots and tuplet, so a dotted quarter triplet has the base quarter.
+	 */
+	pub fn get_base(self) -> Duration {
+		let tuplet_factor = self.tuplet.map(Tuplet::factor).unwrap_or_else(|| Rational::from_integer(1));
+		Duration::from_rational(self.duration / dot_factor(self.dots) / tuplet_factor)
+	}
+
+	pub fn get_dots(self) -> u32 {
+		self.dots
+	}
+
+	pub fn get_tuplet(self) -> Option<Tuplet> {
+		self.tuplet
+	}
+
+	/*
+	 * Returns the base of this duration with the given number of dots, keeping the tuplet.
+	 * A note has at most MAX_DOTS dots, more dots are ignored.
+	 */
+	pub fn with_dots(self, dots: u32) -> Duration {
+		let dots = dots.min(MAX_DOTS);
+		let base = self.get_base();
+		let tuplet_factor = self.tuplet.map(Tuplet::factor).unwrap_or_else(|| Rational::from_integer(1));
+		Duration { duration: base.duration * dot_factor(dots) * tuplet_factor, dots, tuplet: self.tuplet }
+	}
+
+	pub fn dotted(self) -> Duration {
+		self.with_dots(1)
+	}
+
+	pub fn double_dotted(self) -> Duration {
+		self.with_dots(2)
+	}
+
+	/*
+	 * Returns this duration as part of the given tuplet, so a quarter in a triplet lasts 1/6.
+	 */
+	pub fn with_tuplet(self, tuplet: Tuplet) -> Duration {
+		let base = self.get_base();
+		Duration { duration: base.duration * dot_factor(self.dots) * tuplet.factor(), dots: self.dots, tuplet: Some(tuplet) }
+	}
+
+	pub fn triplet(self) -> Duration {
+		self.with_tuplet(Tuplet::triplet())
+	}
+
+	/*
+	 * Whether the duration can be written as a single note: its base is a whole note or a power of two
+	 * fraction of it and it has at most two dots.
+	 */
+	pub fn is_notatable(self) -> bool {
+		let base = self.get_base().duration;
+		*base.numer() == 1 && base.denom().is_power_of_two() && self.dots <= MAX_DOTS
+	}
+
+	/*
+	 * Splits the duration into notatable durations, which are tied together, from the longest to the shortest.
+	 * A duration, which does not fit into the binary note values, becomes part of a tuplet: 5/12 are a half
+	 * and an eighth of a triplet. Bar lines are not taken into account.
+	 */
+	pub fn to_tied_durations(self) -> Vec<Duration> {
+		if self.is_notatable() {
+			return vec![self];
+		}
+		let (written, tuplet) = match self.tuplet {
+			Some(tuplet) => (self.duration / tuplet.factor(), Some(tuplet)),
+			None => {
+				let denominator = *self.duration.denom();
+				let odd_part = denominator >> denominator.trailing_zeros();
+				if odd_part == 1 {
+					(self.duration, None)
+				} else {
+					let tuplet = Tuplet::new(odd_part, odd_part.next_power_of_two() / 2);
+					(self.duration / tuplet.factor(), Some(tuplet))
+				}
+			},
+		};
+		if !written.denom().is_power_of_two() {
+			return vec![self];
+		}
+
+		// a dotted note value can leave a rest, which is up to MAX_DOTS exponents shorter than the duration,
+		// but the denominators of the note values have to fit into u32
+		let max_exponent = (written.denom().trailing_zeros() + MAX_DOTS).min(u32::BITS - 1);
+		// the lengths are counted in the shortest note value, as fractions with these denominators would overflow
+		let whole_units = 1u64 << max_exponent;
+		let mut note_values: Vec<(u64, Duration)> = Vec::new();
+		for exponent in 0..=max_exponent {
+			for dots in (0..=MAX_DOTS.min(max_exponent - exponent)).rev() {
+				let units = ((2 << dots) - 1) << (max_exponent - exponent - dots);
+				note_values.push((units, Duration::new(1, 1 << exponent).with_dots(dots)));
+			}
+		}
+		let mut durations = Vec::new();
+		let mut rest = *written.numer() as u64 * (whole_units / *written.denom() as u64);
+		while rest > 0 {
+			// durations of more than two whole notes start with whole notes
+			let (units, note_value) = if rest >= 2 * whole_units {
+				(whole_units, Duration::whole())
+			} else {
+				*note_values.iter().find(|(units, _)| *units <= rest).unwrap()
+			};
+			rest -= units;
+			durations.push(match tuplet {
+				Some(tuplet) => note_value.with_tuplet(tuplet),
+				None => note_value,
+			});
+		}
+		durations
+	}
+}
+
+impl PartialEq for Duration {
+	fn eq(&self, other: &Duration) -> bool {
+		self.duration == other.duration
+	}
+}
+
+impl PartialOrd for Duration {
+	fn partial_cmp(&self, other: &Duration) -> Option<Ordering> {
+		self.duration.partial_cmp(&other.duration)
+	}
+}
+
+/*
+ * Adding durations ties them, the sum forgets the notation of both durations.
+ */
+impl Add<Duration> for Duration {
+	type Output = Duration;
+
+	fn add(self, duration: Duration) -> Duration {
+		Duration::from_rational(self.duration + duration.duration)
 	}
 }
 
@@ -48,7 +235,7 @@ impl From<Duration> for Rational {
 
 impl From<TimePoint> for Duration {
 	fn from(time_point: TimePoint) -> Duration {
-		Duration { duration: time_point.time_point }
+		Duration::from_rational(time_point.time_point)
 	}
 }
 
@@ -186,6 +373,10 @@ impl RhythmNote {
 		(self.time_point, self.time_point + self.duration)
 	}
 
+	pub fn get_duration(self) -> Duration {
+		self.duration
+	}
+
 	pub fn to_micro_timing<'a, I>(rhythm_notes: I) -> i32
 	where
 		I: Iterator<Item = &'a RhythmNote>
@@ -219,4 +410,55 @@ mod tests {
 		assert_eq!(TimePoint::get_micro_timing(time_points.iter()), 4);
 		println!("{}", time_points[0].get_denominator());
 	}
+
+	#[test]
+	pub fn dotted_and_tuplet_durations() {
+		let dotted_quarter = Duration::quarter().dotted();
+		assert!(dotted_quarter == Duration::new(3, 8));
+		assert_eq!(dotted_quarter.get_dots(), 1);
+		assert!(dotted_quarter.get_base() == Duration::quarter());
+		assert!(Duration::half().double_dotted() == Duration::new(7, 8));
+		assert!(Duration::eighth().triplet() == Duration::new(1, 12));
+		assert!(Duration::sixteenth().with_tuplet(Tuplet::quintuplet()) == Duration::new(1, 20));
+		assert!(Duration::quarter().with_tuplet(Tuplet::new(5, 3)) == Duration::new(3, 20));
+
+		// the tuplet is kept when dotting the duration
+		let dotted_triplet = Duration::eighth().triplet().dotted();
+		assert!(dotted_triplet == Duration::new(1, 8));
+		assert_eq!(dotted_triplet.get_tuplet(), Some(Tuplet::triplet()));
+		assert!(dotted_triplet.is_notatable());
+		assert!(!Duration::new(5, 8).is_notatable());
+
+		// a note has at most two dots
+		assert!(Duration::quarter().with_dots(3) == Duration::quarter().double_dotted());
+		assert_eq!(Duration::quarter().with_dots(40).get_dots(), 2);
+		assert!(Duration::quarter().with_dots(40).is_notatable());
+	}
+
+	#[test]
+	pub fn tied_durations() {
+		let durations = Duration::new(5, 8).to_tied_durations();
+		assert!(durations == vec![Duration::half(), Duration::eighth()]);
+		let durations = Duration::new(15, 16).to_tied_durations();
+		assert!(durations == vec![Duration::half().double_dotted(), Duration::sixteenth()]);
+		assert_eq!(durations[0].get_dots(), 2);
+		assert!(Duration::new(3, 8).to_tied_durations()[0].get_base() == Duration::quarter());
+
+		let durations = Duration::new(5, 12).to_tied_durations();
+		assert!(durations == vec![Duration::half().triplet(), Duration::eighth().triplet()]);
+		assert!(durations.iter().all(|duration| duration.get_tuplet() == Some(Tuplet::triplet())));
+		assert!(Duration::quarter() + Duration::eighth() == Duration::new(3, 8));
+
+		// durations longer than a bar
+		assert!(Duration::new(2, 1).to_tied_durations() == vec![Duration::whole(), Duration::whole()]);
+		assert!(Duration::new(3, 1).to_tied_durations() == vec![Duration::whole(), Duration::whole(), Duration::whole()]);
+		assert!(Duration::new(3, 2).to_tied_durations() == vec![Duration::whole().dotted()]);
+		assert!(Duration::new(9, 4).to_tied_durations() == vec![Duration::whole(), Duration::whole(), Duration::quarter()]);
+		assert!(Duration::new(15, 8).to_tied_durations() == vec![Duration::whole().double_dotted(), Duration::eighth()]);
+		// the shortest note values still fit into u32
+		assert!(Duration::new(3, 1 << 31).to_tied_durations() == vec![Duration::new(1, 1 << 30).dotted()]);
+		assert!(Duration::new(5, 1 << 31).to_tied_durations() == vec![Duration::new(1, 1 << 29), Duration::new(1, 1 << 31)]);
+		let durations = Duration::new(7, 3).to_tied_durations();
+		assert!(durations.iter().fold(Duration::new(0, 1), |sum, duration| sum + *duration) == Duration::new(7, 3));
+	}
 }
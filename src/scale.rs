@@ -1,11 +1,23 @@
+use std::fmt;
+use std::ops::Index;
+use std::slice::Iter;
+use std::iter::Rev;
+
 use crate::keys::Key;
-use crate::intervals::Interval;
-use crate::pitch::Pitch;
+use crate::intervals::{Interval, UnspecifiedInterval};
+use crate::pitch::{Pitch, PitchRange};
+use crate::spelled_key::SpelledKey;
+use crate::math::neg_modulo;
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct ScaleTemplate {
 	pub intervals: Vec<Interval>,
 }
 
+/*
+ * The keys of a scale, starting with its keynote. A scale always has at least one key.
+ */
+#[derive(Clone, Debug, PartialEq)]
 pub struct KeyScale {
 	pub keys: Vec<Key>,
 }
@@ -67,13 +79,366 @@ impl ScaleTemplate {
 							 Interval { halftones: 11 }];
 		ScaleTemplate { intervals }
 	}
+
+	pub fn from_halftones(halftones: &[i32]) -> ScaleTemplate {
+		ScaleTemplate { intervals: halftones.iter().map(|halftones| Interval { halftones: *halftones }).collect() }
+	}
+
+	// church modes
+	pub fn ionian() -> ScaleTemplate { ScaleTemplate::major() }
+	pub fn dorian() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 2, 3, 5, 7, 9, 10]) }
+	pub fn phrygian() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 1, 3, 5, 7, 8, 10]) }
+	pub fn lydian() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 2, 4, 6, 7, 9, 11]) }
+	pub fn mixolydian() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 2, 4, 5, 7, 9, 10]) }
+	pub fn aeolian() -> ScaleTemplate { ScaleTemplate::minor() }
+	pub fn locrian() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 1, 3, 5, 6, 8, 10]) }
+
+	// melodic minor and its modes
+	pub fn minor_melodic() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 2, 3, 5, 7, 9, 11]) }
+	pub fn dorian_flat_second() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 1, 3, 5, 7, 9, 10]) }
+	pub fn lydian_augmented() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 2, 4, 6, 8, 9, 11]) }
+	pub fn lydian_dominant() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 2, 4, 6, 7, 9, 10]) }
+	pub fn mixolydian_flat_sixth() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 2, 4, 5, 7, 8, 10]) }
+	pub fn locrian_natural_second() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 2, 3, 5, 6, 8, 10]) }
+	pub fn altered() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 1, 3, 4, 6, 8, 10]) }
+
+	pub fn major_pentatonic() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 2, 4, 7, 9]) }
+	pub fn minor_pentatonic() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 3, 5, 7, 10]) }
+	pub fn blues() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 3, 5, 6, 7, 10]) }
+
+	// symmetric scales
+	pub fn whole_tone() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 2, 4, 6, 8, 10]) }
+	pub fn octatonic_half_whole() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 1, 3, 4, 6, 7, 9, 10]) }
+	pub fn octatonic_whole_half() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 2, 3, 5, 6, 8, 9, 11]) }
+
+	pub fn hungarian_minor() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 2, 3, 6, 7, 8, 11]) }
+	pub fn double_harmonic() -> ScaleTemplate { ScaleTemplate::from_halftones(&[0, 1, 4, 5, 7, 8, 11]) }
+
+	/*
+	 * Returns the nth mode of this scale, which starts on the nth degree. So the first rotation of
+	 * the major scale is dorian and the fifth rotation is aeolian.
+	 */
+	pub fn rotate(&self, n: i32) -> ScaleTemplate {
+		if self.intervals.is_empty() {
+			return ScaleTemplate::new();
+		}
+		let num_intervals = self.intervals.len();
+		let start = neg_modulo(n, num_intervals as u32) as usize;
+		let start_interval = self.intervals[start];
+		let intervals = (0..num_intervals)
+			.map(|index| self.intervals[(start + index) % num_intervals])
+			.map(|interval| Interval { halftones: interval.halftones - start_interval.halftones }.to_base_interval())
+			.collect();
+		ScaleTemplate { intervals }
+	}
+
+	/*
+	 * Returns all scale templates with their names, which are understood by ScaleTemplate::from_name.
+	 */
+	pub fn all() -> Vec<(&'static str, ScaleTemplate)> {
+		SCALE_TEMPLATES.iter().map(|(name, scale_template)| (*name, scale_template())).collect()
+	}
+
+	/*
+	 * Looks up a scale template by its name, like "dorian", "Melodic Minor" or "octatonic-half-whole".
+	 * Case, spaces, hyphens and underscores are ignored.
+	 */
+	pub fn from_name(name: &str) -> Option<ScaleTemplate> {
+		let normalized: String = name.chars()
+			.filter(|c| !matches!(c, ' ' | '-' | '_'))
+			.map(|c| c.to_ascii_lowercase())
+			.collect();
+		SCALE_TEMPLATES.iter()
+			.chain(SCALE_TEMPLATE_ALIASES.iter())
+			.find(|(template_name, _)| template_name.replace('_', "") == normalized)
+			.map(|(_, scale_template)| scale_template())
+	}
 }
 
+type ScaleTemplateConstructor = fn() -> ScaleTemplate;
+
+const SCALE_TEMPLATES: &[(&str, ScaleTemplateConstructor)] = &[
+	("chromatic", ScaleTemplate::chromatic_scale),
+	("major", ScaleTemplate::major),
+	("minor", ScaleTemplate::minor),
+	("minor_harmonic", ScaleTemplate::minor_harmonic),
+	("minor_melodic", ScaleTemplate::minor_melodic),
+	("ionian", ScaleTemplate::ionian),
+	("dorian", ScaleTemplate::dorian),
+	("phrygian", ScaleTemplate::phrygian),
+	("lydian", ScaleTemplate::lydian),
+	("mixolydian", ScaleTemplate::mixolydian),
+	("aeolian", ScaleTemplate::aeolian),
+	("locrian", ScaleTemplate::locrian),
+	("dorian_flat_second", ScaleTemplate::dorian_flat_second),
+	("lydian_augmented", ScaleTemplate::lydian_augmented),
+	("lydian_dominant", ScaleTemplate::lydian_dominant),
+	("mixolydian_flat_sixth", ScaleTemplate::mixolydian_flat_sixth),
+	("locrian_natural_second", ScaleTemplate::locrian_natural_second),
+	("altered", ScaleTemplate::altered),
+	("major_pentatonic", ScaleTemplate::major_pentatonic),
+	("minor_pentatonic", ScaleTemplate::minor_pentatonic),
+	("blues", ScaleTemplate::blues),
+	("whole_tone", ScaleTemplate::whole_tone),
+	("octatonic_half_whole", ScaleTemplate::octatonic_half_whole),
+	("octatonic_whole_half", ScaleTemplate::octatonic_whole_half),
+	("hungarian_minor", ScaleTemplate::hungarian_minor),
+	("double_harmonic", ScaleTemplate::double_harmonic),
+];
+
+const SCALE_TEMPLATE_ALIASES: &[(&str, ScaleTemplateConstructor)] = &[
+	("natural_minor", ScaleTemplate::minor),
+	("harmonic_minor", ScaleTemplate::minor_harmonic),
+	("melodic_minor", ScaleTemplate::minor_melodic),
+	("phrygian_natural_sixth", ScaleTemplate::dorian_flat_second),
+	("overtone", ScaleTemplate::lydian_dominant),
+	("aeolian_dominant", ScaleTemplate::mixolydian_flat_sixth),
+	("half_diminished", ScaleTemplate::locrian_natural_second),
+	("super_locrian", ScaleTemplate::altered),
+	("diminished", ScaleTemplate::octatonic_whole_half),
+	("dominant_diminished", ScaleTemplate::octatonic_half_whole),
+	("gypsy_minor", ScaleTemplate::hungarian_minor),
+	("byzantine", ScaleTemplate::double_harmonic),
+];
+
 impl KeyScale {
+	/*
+	 * An empty scale template gives the scale, which only consists of the keynote.
+	 */
 	pub fn from_scale_template(scale_template: &ScaleTemplate, keynote: Key) -> KeyScale {
+		if scale_template.intervals.is_empty() {
+			return KeyScale { keys: vec![keynote] };
+		}
 		let keys = scale_template.intervals.iter().map(|interval| keynote + *interval).collect();
 		KeyScale { keys }
 	}
+
+	pub fn keynote(&self) -> Key {
+		self.keys[0]
+	}
+
+	/*
+	 * Returns the intervals of this scale relative to its keynote.
+	 */
+	pub fn to_scale_template(&self) -> ScaleTemplate {
+		ScaleTemplate { intervals: self.keys.iter().map(|key| Key::to_interval(self.keynote(), *key)).collect() }
+	}
+
+	/*
+	 * Returns the scale with the same keys, which starts on the given degree of this scale.
+	 */
+	pub fn mode(&self, degree: i32) -> KeyScale {
+		let keys = (0..self.keys.len() as i32).map(|index| self.key_at(degree + index)).collect();
+		KeyScale { keys }
+	}
+
+	/*
+	 * Returns the relative minor scale of a major scale and the relative major scale of a natural
+	 * minor scale. Returns None for all other scales.
+	 */
+	pub fn relative(&self) -> Option<KeyScale> {
+		let scale_template = self.to_scale_template();
+		if scale_template == ScaleTemplate::major() {
+			Some(self.mode(5))
+		} else if scale_template == ScaleTemplate::minor() {
+			Some(self.mode(2))
+		} else {
+			None
+		}
+	}
+
+	/*
+	 * Returns the natural minor scale on the same keynote for a major scale and the major scale on
+	 * the same keynote for a natural, harmonic or melodic minor scale. Returns None for all other scales.
+	 */
+	pub fn parallel(&self) -> Option<KeyScale> {
+		let scale_template = self.to_scale_template();
+		if scale_template == ScaleTemplate::major() {
+			Some(KeyScale::from_scale_template(&ScaleTemplate::minor(), self.keynote()))
+		} else if scale_template == ScaleTemplate::minor()
+			|| scale_template == ScaleTemplate::minor_harmonic()
+			|| scale_template == ScaleTemplate::minor_melodic() {
+			Some(KeyScale::from_scale_template(&ScaleTemplate::major(), self.keynote()))
+		} else {
+			None
+		}
+	}
+
+	/*
+	 * Returns all scales of ScaleTemplate::all() on every keynote, which contain all the given keys.
+	 * Scales with different names but the same keys in the same order (like major and ionian) are
+	 * only returned once, with the name which comes first in ScaleTemplate::all().
+	 */
+	pub fn containing(keys: &[Key]) -> Vec<(&'static str, KeyScale)> {
+		let mut scales: Vec<(&'static str, KeyScale)> = Vec::new();
+		for (name, scale_template) in ScaleTemplate::all() {
+			for value in 0..Interval::octave().halftones {
+				let scale = KeyScale::from_scale_template(&scale_template, Key::new(value as u8));
+				let contains_keys = keys.iter().all(|key| scale.keys.contains(key));
+				if contains_keys && !scales.iter().any(|(_, s)| *s == scale) {
+					scales.push((name, scale));
+				}
+			}
+		}
+		scales
+	}
+
+	/*
+	 * Returns the degree of the given key in this scale, starting with 0 for the keynote,
+	 * or None if the key is not part of this scale.
+	 */
+	pub fn degree_of(&self, key: Key) -> Option<usize> {
+		self.keys.iter().position(|k| *k == key)
+	}
+
+	/*
+	 * Returns the key at the given degree. Degrees outside of the scale wrap around,
+	 * so in a heptatonic scale degree 7 is the keynote again and degree -1 the seventh.
+	 */
+	pub fn key_at(&self, degree: i32) -> Key {
+		self.keys[neg_modulo(degree, self.keys.len() as u32) as usize]
+	}
+
+	/*
+	 * Moves the given pitch the given number of scale steps up (or down for negative steps).
+	 * Returns None if the pitch is not part of this scale.
+	 */
+	pub fn transpose_pitch(&self, pitch: Pitch, steps: i32) -> Option<Pitch> {
+		let degree = self.degree_of(pitch.into())? as i32;
+		let keynote = self.keys[0];
+		let keynote_pitch = pitch - Key::to_interval(keynote, self.keys[degree as usize]);
+
+		let target_degree = degree + steps;
+		let num_octaves = (target_degree - neg_modulo(target_degree, self.keys.len() as u32) as i32) / self.keys.len() as i32;
+		let interval = Key::to_interval(keynote, self.key_at(target_degree));
+		Some(keynote_pitch + interval + Interval { halftones: num_octaves * Interval::octave().halftones })
+	}
+
+	/*
+	 * Returns the pitch of this scale nearest to the given pitch. If two pitches are equally near,
+	 * the lower one is returned.
+	 */
+	pub fn snap_pitch(&self, pitch: Pitch) -> Pitch {
+		for distance in 0..Interval::octave().halftones {
+			for candidate in [pitch - Interval { halftones: distance }, pitch + Interval { halftones: distance }].iter() {
+				if self.degree_of((*candidate).into()).is_some() {
+					return *candidate;
+				}
+			}
+		}
+		pitch
+	}
+
+	/*
+	 * Spells the keys of this scale.
+	 * Heptatonic scales use every letter exactly once, starting with the spelling of the keynote which
+	 * needs the fewest accidentals (so b-flat major instead of a-sharp major). Ties are broken by the
+	 * usual spelling of the keynote.
+	 * Other scales are spelled with sharps or flats depending on the usual spelling of the keynote.
+	 */
+	pub fn spelled_keys(&self) -> Vec<SpelledKey> {
+		let keynote = match self.keys.first() {
+			Some(keynote) => *keynote,
+			None => return Vec::new(),
+		};
+
+		if self.keys.len() == UnspecifiedInterval::octave().value as usize {
+			let mut best_spelling: Vec<SpelledKey> = Vec::new();
+			let mut best_num_accidentals = i32::MAX;
+			let usual_letter = SpelledKey::from_key(keynote).letter;
+			for letter_offset in 0..UnspecifiedInterval::octave().value {
+				let keynote_letter = usual_letter + UnspecifiedInterval::new(letter_offset);
+				let spelling: Vec<SpelledKey> = self.keys.iter().enumerate()
+					.map(|(step, key)| SpelledKey::from_key_and_letter(*key, keynote_letter + UnspecifiedInterval::new(step as i32)))
+					.collect();
+				let num_accidentals: i32 = spelling.iter().map(|spelled_key| (spelled_key.accidentals as i32).abs()).sum();
+				if num_accidentals < best_num_accidentals {
+					best_num_accidentals = num_accidentals;
+					best_spelling = spelling;
+				}
+			}
+			return best_spelling;
+		}
+
+		if SpelledKey::from_key(keynote).accidentals < 0 {
+			self.keys.iter().map(|key| SpelledKey::from_key_with_flats(*key)).collect()
+		} else {
+			self.keys.iter().map(|key| SpelledKey::from_key_with_sharps(*key)).collect()
+		}
+	}
+}
+
+impl PitchScale {
+	/*
+	 * Contains all pitches of the scale inside the pitch range, with the lower pitch inclusive and the
+	 * upper pitch exclusive like in PitchRange::inner_pitches.
+	 */
+	pub fn from_scale_and_pitch_range(scale: &KeyScale, pitch_range: PitchRange) -> PitchScale {
+		let mut pitches = Vec::new();
+		for key in scale.keys.iter() {
+			pitches.extend(pitch_range.inner_pitches(*key));
+		}
+		pitches.sort();
+		pitches.dedup();
+		PitchScale { pitches }
+	}
+
+	/*
+	 * Contains all pitches of the scale from the start pitch up to the given number of octaves above,
+	 * both inclusive. So one octave of c major starting on c contains both c's.
+	 */
+	pub fn from_scale_and_octaves(scale: &KeyScale, start: Pitch, num_octaves: u32) -> PitchScale {
+		let end = start + Interval { halftones: num_octaves as i32 * Interval::octave().halftones + 1 };
+		PitchScale::from_scale_and_pitch_range(scale, PitchRange::from_pitches(start, end))
+	}
+
+	/*
+	 * Returns the pitch at the given absolute scale degree, counted from the lowest pitch of this scale
+	 * without wrapping around.
+	 */
+	pub fn get(&self, degree: usize) -> Option<Pitch> {
+		self.pitches.get(degree).cloned()
+	}
+
+	pub fn len(&self) -> usize {
+		self.pitches.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.pitches.is_empty()
+	}
+
+	pub fn ascending(&self) -> Iter<'_, Pitch> {
+		self.pitches.iter()
+	}
+
+	pub fn descending(&self) -> Rev<Iter<'_, Pitch>> {
+		self.pitches.iter().rev()
+	}
+}
+
+impl Index<usize> for PitchScale {
+	type Output = Pitch;
+
+	fn index(&self, degree: usize) -> &Pitch {
+		&self.pitches[degree]
+	}
+}
+
+impl<'a> IntoIterator for &'a PitchScale {
+	type Item = &'a Pitch;
+	type IntoIter = Iter<'a, Pitch>;
+
+	fn into_iter(self) -> Iter<'a, Pitch> {
+		self.ascending()
+	}
+}
+
+impl fmt::Display for KeyScale {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let names: Vec<String> = self.spelled_keys().iter().map(|spelled_key| spelled_key.to_string()).collect();
+		write!(f, "{}", names.join(" "))
+	}
 }
 
 #[cfg(test)]
@@ -106,4 +471,154 @@ mod tests {
 		let minor_harmonic_a_keys = vec![Key::a(), Key::b(), Key::c(), Key::d(), Key::e(), Key::f(), Key::gis()];
 		assert_eq!(minor_harmonic_a_scale.keys, minor_harmonic_a_keys);
 	}
+
+	#[test]
+	pub fn scale_catalogue() {
+		let dorian_d_scale = KeyScale::from_scale_template(&ScaleTemplate::dorian(), Key::d());
+		assert_eq!(dorian_d_scale.to_string(), "d e f g a b c");
+
+		let altered_g_scale = KeyScale::from_scale_template(&ScaleTemplate::altered(), Key::g());
+		assert_eq!(altered_g_scale.to_string(), "g ab bb cb db eb f");
+
+		let blues_a_scale = KeyScale::from_scale_template(&ScaleTemplate::blues(), Key::a());
+		assert_eq!(blues_a_scale.keys, vec![Key::a(), Key::c(), Key::d(), Key::dis(), Key::e(), Key::g()]);
+
+		for (_, scale_template) in ScaleTemplate::all() {
+			assert_eq!(scale_template.intervals[0], Interval::prime());
+			assert!(scale_template.intervals.windows(2).all(|w| w[0].halftones < w[1].halftones));
+			assert!(scale_template.intervals.last().unwrap().halftones < Interval::octave().halftones);
+		}
+	}
+
+	#[test]
+	pub fn scale_template_from_name() {
+		assert_eq!(ScaleTemplate::from_name("dorian"), Some(ScaleTemplate::dorian()));
+		assert_eq!(ScaleTemplate::from_name("Melodic Minor"), Some(ScaleTemplate::minor_melodic()));
+		assert_eq!(ScaleTemplate::from_name("octatonic-half-whole"), Some(ScaleTemplate::octatonic_half_whole()));
+		assert_eq!(ScaleTemplate::from_name("WholeTone"), Some(ScaleTemplate::whole_tone()));
+		assert_eq!(ScaleTemplate::from_name("bebop"), None);
+		for (name, scale_template) in ScaleTemplate::all() {
+			assert_eq!(ScaleTemplate::from_name(name), Some(scale_template));
+		}
+	}
+
+	#[test]
+	pub fn scale_degrees() {
+		let major_c_scale = KeyScale::from_scale_template(&ScaleTemplate::major(), Key::c());
+		assert_eq!(major_c_scale.degree_of(Key::c()), Some(0));
+		assert_eq!(major_c_scale.degree_of(Key::b()), Some(6));
+		assert_eq!(major_c_scale.degree_of(Key::cis()), None);
+		assert_eq!(major_c_scale.key_at(4), Key::g());
+		assert_eq!(major_c_scale.key_at(9), Key::e());
+		assert_eq!(major_c_scale.key_at(-1), Key::b());
+
+		let keynote_scale = KeyScale::from_scale_template(&ScaleTemplate::new(), Key::d());
+		assert_eq!(keynote_scale.keynote(), Key::d());
+		assert_eq!(keynote_scale.key_at(3), Key::d());
+		assert_eq!(keynote_scale.mode(2), keynote_scale);
+	}
+
+	#[test]
+	pub fn scale_transpose_pitch() {
+		let major_c_scale = KeyScale::from_scale_template(&ScaleTemplate::major(), Key::c());
+		let c = Pitch { value: 39 };
+		assert_eq!(major_c_scale.transpose_pitch(c, 2), Some(Pitch { value: 43 }));
+		assert_eq!(major_c_scale.transpose_pitch(c, 7), Some(Pitch { value: 51 }));
+		assert_eq!(major_c_scale.transpose_pitch(c, -1), Some(Pitch { value: 38 }));
+		assert_eq!(major_c_scale.transpose_pitch(c, -8), Some(Pitch { value: 26 }));
+		// from b up to d and from a up to c are both thirds
+		assert_eq!(major_c_scale.transpose_pitch(Pitch { value: 38 }, 2), Some(Pitch { value: 41 }));
+		assert_eq!(major_c_scale.transpose_pitch(Pitch { value: 36 }, 2), Some(Pitch { value: 39 }));
+		assert_eq!(major_c_scale.transpose_pitch(Pitch { value: 40 }, 1), None);
+	}
+
+	#[test]
+	pub fn scale_snap_pitch() {
+		let major_c_scale = KeyScale::from_scale_template(&ScaleTemplate::major(), Key::c());
+		assert_eq!(major_c_scale.snap_pitch(Pitch { value: 39 }), Pitch { value: 39 });
+		assert_eq!(major_c_scale.snap_pitch(Pitch { value: 40 }), Pitch { value: 39 });
+
+		let pentatonic_c_scale = KeyScale::from_scale_template(&ScaleTemplate::major_pentatonic(), Key::c());
+		assert_eq!(pentatonic_c_scale.snap_pitch(Pitch { value: 44 }), Pitch { value: 43 });
+		assert_eq!(pentatonic_c_scale.snap_pitch(Pitch { value: 45 }), Pitch { value: 46 });
+	}
+
+	#[test]
+	pub fn pitch_scale_from_pitch_range() {
+		let major_a_scale = KeyScale::from_scale_template(&ScaleTemplate::major(), Key::a());
+		let pitch_range = PitchRange { lower: Pitch { value: 10 }, upper: Pitch { value: 17 } };
+		let pitch_scale = PitchScale::from_scale_and_pitch_range(&major_a_scale, pitch_range);
+		let asserted_pitches: Vec<Pitch> = [11, 12, 14, 16].iter().map(|value| Pitch { value: *value }).collect();
+		assert_eq!(pitch_scale.pitches, asserted_pitches);
+		assert_eq!(pitch_scale[1], Pitch { value: 12 });
+		assert_eq!(pitch_scale.get(4), None);
+	}
+
+	#[test]
+	pub fn pitch_scale_from_octaves() {
+		let minor_c_scale = KeyScale::from_scale_template(&ScaleTemplate::minor(), Key::c());
+		let pitch_scale = PitchScale::from_scale_and_octaves(&minor_c_scale, Pitch { value: 39 }, 2);
+		assert_eq!(pitch_scale.len(), 15);
+		assert_eq!(pitch_scale[0], Pitch { value: 39 });
+		assert_eq!(pitch_scale[8], Pitch { value: 53 });
+		assert_eq!(pitch_scale[14], Pitch { value: 63 });
+
+		let ascending: Vec<Pitch> = pitch_scale.ascending().cloned().collect();
+		let mut descending: Vec<Pitch> = pitch_scale.descending().cloned().collect();
+		descending.reverse();
+		assert_eq!(ascending, descending);
+		assert!(ascending.windows(2).all(|w| w[0] < w[1]));
+	}
+
+	#[test]
+	pub fn scale_template_rotation() {
+		assert_eq!(ScaleTemplate::major().rotate(1), ScaleTemplate::dorian());
+		assert_eq!(ScaleTemplate::major().rotate(5), ScaleTemplate::minor());
+		assert_eq!(ScaleTemplate::major().rotate(-1), ScaleTemplate::locrian());
+		assert_eq!(ScaleTemplate::minor_melodic().rotate(6), ScaleTemplate::altered());
+		assert_eq!(ScaleTemplate::major_pentatonic().rotate(4), ScaleTemplate::minor_pentatonic());
+		assert_eq!(ScaleTemplate::major().rotate(7), ScaleTemplate::major());
+	}
+
+	#[test]
+	pub fn scale_relations() {
+		let major_c_scale = KeyScale::from_scale_template(&ScaleTemplate::major(), Key::c());
+		let minor_a_scale = KeyScale::from_scale_template(&ScaleTemplate::minor(), Key::a());
+		let minor_c_scale = KeyScale::from_scale_template(&ScaleTemplate::minor(), Key::c());
+		assert_eq!(major_c_scale.relative(), Some(minor_a_scale.clone()));
+		assert_eq!(minor_a_scale.relative(), Some(major_c_scale.clone()));
+		assert_eq!(major_c_scale.parallel(), Some(minor_c_scale));
+		let minor_harmonic_c_scale = KeyScale::from_scale_template(&ScaleTemplate::minor_harmonic(), Key::c());
+		assert_eq!(minor_harmonic_c_scale.parallel(), Some(major_c_scale.clone()));
+		assert_eq!(minor_harmonic_c_scale.relative(), None);
+		assert_eq!(major_c_scale.mode(1), KeyScale::from_scale_template(&ScaleTemplate::dorian(), Key::d()));
+	}
+
+	#[test]
+	pub fn scales_containing_keys() {
+		let scales = KeyScale::containing(&[Key::c(), Key::e(), Key::g(), Key::b(), Key::fis()]);
+		let names: Vec<String> = scales.iter().map(|(name, scale)| format!("{} {}", scale.spelled_keys()[0], name)).collect();
+		assert!(names.contains(&"g major".to_string()));
+		assert!(names.contains(&"e minor".to_string()));
+		assert!(names.contains(&"c lydian".to_string()));
+		assert!(!names.contains(&"c major".to_string()));
+		// e minor and e aeolian are the same scale
+		assert!(!names.contains(&"e aeolian".to_string()));
+		assert!(names.contains(&"c chromatic".to_string()));
+	}
+
+	#[test]
+	pub fn spelled_scales() {
+		let major_b_flat_scale = KeyScale::from_scale_template(&ScaleTemplate::major(), Key::ais());
+		assert_eq!(major_b_flat_scale.to_string(), "bb c d eb f g a");
+
+		let minor_e_flat_scale = KeyScale::from_scale_template(&ScaleTemplate::minor(), Key::dis());
+		assert_eq!(minor_e_flat_scale.to_string(), "eb f gb ab bb cb db");
+
+		let minor_gis_scale = KeyScale::from_scale_template(&ScaleTemplate::minor(), Key::gis());
+		assert_eq!(minor_gis_scale.to_string(), "g# a# b c# d# e f#");
+
+		let minor_harmonic_cis_scale = KeyScale::from_scale_template(&ScaleTemplate::minor_harmonic(), Key::cis());
+		assert_eq!(minor_harmonic_cis_scale.to_string(), "c# d# e f# g# a b#");
+	}
 }
@@ -0,0 +1,261 @@
+use std::ops::{Add, Sub};
+use std::convert::From;
+use std::fmt;
+
+use crate::keys::Key;
+use crate::intervals::{Interval, UnspecifiedInterval, QualifiedInterval};
+use crate::math::neg_modulo;
+
+const NUM_LETTERS: u8 = 7;
+
+/*
+ * The seven letter names of the diatonic scale, starting with A like the keyboard in pitch.rs.
+ */
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Letter {
+	A,
+	B,
+	C,
+	D,
+	E,
+	F,
+	G,
+}
+
+/*
+ * A key with its spelling: a letter name and a number of accidentals.
+ * Positive accidentals are sharps, negative accidentals are flats.
+ * So a-sharp and b-flat are different SpelledKeys, which map to the same Key.
+ */
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub struct SpelledKey {
+	pub letter: Letter,
+	pub accidentals: i8,
+}
+
+impl Letter {
+	pub fn from_index(index: i32) -> Letter {
+		match neg_modulo(index, NUM_LETTERS as u32) {
+			0 => Letter::A,
+			1 => Letter::B,
+			2 => Letter::C,
+			3 => Letter::D,
+			4 => Letter::E,
+			5 => Letter::F,
+			6 => Letter::G,
+			_ => unimplemented!(),
+		}
+	}
+
+	pub fn get_index(self) -> u8 {
+		match self {
+			Letter::A => 0,
+			Letter::B => 1,
+			Letter::C => 2,
+			Letter::D => 3,
+			Letter::E => 4,
+			Letter::F => 5,
+			Letter::G => 6,
+		}
+	}
+
+	/*
+	 * Returns the Key of the unaltered letter.
+	 */
+	pub fn to_key(self) -> Key {
+		match self {
+			Letter::A => Key::a(),
+			Letter::B => Key::b(),
+			Letter::C => Key::c(),
+			Letter::D => Key::d(),
+			Letter::E => Key::e(),
+			Letter::F => Key::f(),
+			Letter::G => Key::g(),
+		}
+	}
+}
+
+impl Add<UnspecifiedInterval> for Letter {
+	type Output = Letter;
+
+	fn add(self, interval: UnspecifiedInterval) -> Letter {
+		Letter::from_index(self.get_index() as i32 + interval.value)
+	}
+}
+
+impl Sub<UnspecifiedInterval> for Letter {
+	type Output = Letter;
+
+	fn sub(self, interval: UnspecifiedInterval) -> Letter {
+		Letter::from_index(self.get_index() as i32 - interval.value)
+	}
+}
+
+impl fmt::Display for Letter {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Letter::A => write!(f, "a"),
+			Letter::B => write!(f, "b"),
+			Letter::C => write!(f, "c"),
+			Letter::D => write!(f, "d"),
+			Letter::E => write!(f, "e"),
+			Letter::F => write!(f, "f"),
+			Letter::G => write!(f, "g"),
+		}
+	}
+}
+
+impl SpelledKey {
+	pub fn new(letter: Letter, accidentals: i8) -> SpelledKey {
+		SpelledKey { letter, accidentals }
+	}
+
+	pub fn natural(letter: Letter) -> SpelledKey {
+		SpelledKey { letter, accidentals: 0 }
+	}
+
+	pub fn to_key(self) -> Key {
+		Key::new(neg_modulo(self.letter.to_key().get_value() as i32 + self.accidentals as i32, 12) as u8)
+	}
+
+	/*
+	 * Spells the given key with the given letter, using as many accidentals as needed.
+	 * For example Key::ais() spelled with Letter::B is b-flat and Key::a() spelled with Letter::B is b-double-flat.
+	 */
+	pub fn from_key_and_letter(key: Key, letter: Letter) -> SpelledKey {
+		let mut accidentals = Key::to_interval(letter.to_key(), key).halftones;
+		if accidentals > 6 {
+			accidentals -= 12;
+		}
+		SpelledKey { letter, accidentals: accidentals as i8 }
+	}
+
+	pub fn from_key_with_sharps(key: Key) -> SpelledKey {
+		match key.get_value() {
+			0 | 1 => SpelledKey::from_key_and_letter(key, Letter::A),
+			2 => SpelledKey::natural(Letter::B),
+			3 | 4 => SpelledKey::from_key_and_letter(key, Letter::C),
+			5 | 6 => SpelledKey::from_key_and_letter(key, Letter::D),
+			7 => SpelledKey::natural(Letter::E),
+			8 | 9 => SpelledKey::from_key_and_letter(key, Letter::F),
+			10 | 11 => SpelledKey::from_key_and_letter(key, Letter::G),
+			_ => unimplemented!(),
+		}
+	}
+
+	pub fn from_key_with_flats(key: Key) -> SpelledKey {
+		match key.get_value() {
+			0 => SpelledKey::natural(Letter::A),
+			1 | 2 => SpelledKey::from_key_and_letter(key, Letter::B),
+			3 => SpelledKey::natural(Letter::C),
+			4 | 5 => SpelledKey::from_key_and_letter(key, Letter::D),
+			6 | 7 => SpelledKey::from_key_and_letter(key, Letter::E),
+			8 => SpelledKey::natural(Letter::F),
+			9 | 10 => SpelledKey::from_key_and_letter(key, Letter::G),
+			11 => SpelledKey::from_key_and_letter(key, Letter::A),
+			_ => unimplemented!(),
+		}
+	}
+
+	/*
+	 * Spells the key the way it is usually spelled without further context:
+	 * c-sharp and f-sharp with sharps, b-flat, e-flat and a-flat with flats.
+	 */
+	pub fn from_key(key: Key) -> SpelledKey {
+		match key.get_value() {
+			1 | 6 | 11 => SpelledKey::from_key_with_flats(key),
+			_ => SpelledKey::from_key_with_sharps(key),
+		}
+	}
+
+	/*
+	 * Returns the key, which lies the given interval above this key and is spelled with the letter
+	 * the given number of steps above this letter.
+	 * For example b-flat above by a major third and a third is d, not c-double-sharp.
+	 */
+	pub fn above(self, interval: Interval, steps: UnspecifiedInterval) -> SpelledKey {
+		SpelledKey::from_key_and_letter(self.to_key() + interval, self.letter + steps)
+	}
+
+	/*
+	 * Same as SpelledKey::above, but the step count is derived from the interval.
+	 */
+	pub fn above_interval(self, interval: Interval) -> SpelledKey {
+		self.above(interval, interval.to_unspecified_interval())
+	}
+}
+
+impl Add<QualifiedInterval> for SpelledKey {
+	type Output = SpelledKey;
+
+	fn add(self, interval: QualifiedInterval) -> SpelledKey {
+		self.above(interval.to_interval(), interval.unspecified_interval)
+	}
+}
+
+impl From<SpelledKey> for Key {
+	fn from(spelled_key: SpelledKey) -> Key {
+		spelled_key.to_key()
+	}
+}
+
+impl From<Key> for SpelledKey {
+	fn from(key: Key) -> SpelledKey {
+		SpelledKey::from_key(key)
+	}
+}
+
+impl fmt::Display for SpelledKey {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.letter.fmt(f)?;
+		let accidental = if self.accidentals < 0 { "b" } else { "#" };
+		for _ in 0..self.accidentals.abs() {
+			write!(f, "{}", accidental)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn spelled_key_to_key() {
+		assert_eq!(SpelledKey::new(Letter::B, -1).to_key(), Key::ais());
+		assert_eq!(SpelledKey::new(Letter::A, 1).to_key(), Key::ais());
+		assert_eq!(SpelledKey::new(Letter::C, -1).to_key(), Key::b());
+		assert_eq!(SpelledKey::new(Letter::F, 2).to_key(), Key::g());
+		assert_eq!(SpelledKey::new(Letter::A, -2).to_key(), Key::g());
+	}
+
+	#[test]
+	pub fn key_to_spelled_key() {
+		assert_eq!(SpelledKey::from_key_and_letter(Key::ais(), Letter::B), SpelledKey::new(Letter::B, -1));
+		assert_eq!(SpelledKey::from_key_and_letter(Key::a(), Letter::B), SpelledKey::new(Letter::B, -2));
+		assert_eq!(SpelledKey::from_key_and_letter(Key::g(), Letter::F), SpelledKey::new(Letter::F, 2));
+		assert_eq!(SpelledKey::from_key_with_flats(Key::dis()), SpelledKey::new(Letter::E, -1));
+		assert_eq!(SpelledKey::from_key_with_sharps(Key::dis()), SpelledKey::new(Letter::D, 1));
+		assert_eq!(SpelledKey::from_key(Key::ais()), SpelledKey::new(Letter::B, -1));
+	}
+
+	#[test]
+	pub fn spelled_key_above() {
+		let b_flat = SpelledKey::new(Letter::B, -1);
+		assert_eq!(b_flat.above_interval(Interval::major_third()), SpelledKey::natural(Letter::D));
+		assert_eq!(b_flat.above_interval(Interval::fifth()), SpelledKey::natural(Letter::F));
+		assert_eq!(b_flat.above_interval(Interval::minor_third()), SpelledKey::new(Letter::D, -1));
+		let e_sharp = SpelledKey::new(Letter::E, 1);
+		assert_eq!(e_sharp.above(Interval::major_third(), UnspecifiedInterval::third()), SpelledKey::new(Letter::G, 2));
+		let c = SpelledKey::natural(Letter::C);
+		assert_eq!(c + "A4".parse::<QualifiedInterval>().unwrap(), SpelledKey::new(Letter::F, 1));
+		assert_eq!(c + "d5".parse::<QualifiedInterval>().unwrap(), SpelledKey::new(Letter::G, -1));
+	}
+
+	#[test]
+	pub fn spelled_key_display() {
+		assert_eq!(SpelledKey::new(Letter::B, -1).to_string(), "bb");
+		assert_eq!(SpelledKey::new(Letter::F, 2).to_string(), "f##");
+		assert_eq!(SpelledKey::natural(Letter::C).to_string(), "c");
+	}
+}
@@ -0,0 +1,244 @@
+use std::fmt;
+
+use crate::keys::Key;
+use crate::intervals::Interval;
+use crate::pitch::{Pitch, Octave};
+use crate::note::Note;
+use crate::rhythm::generator::RhythmPattern;
+use crate::pitch_class_set::PitchClassSet;
+
+const ROW_LENGTH: usize = 12;
+
+/*
+ * The four forms of a tone row: the prime form, its retrograde (backwards), its inversion (every interval
+ * mirrored) and the retrograde of the inversion.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RowForm {
+	Prime,
+	Retrograde,
+	Inversion,
+	RetrogradeInversion,
+}
+
+/*
+ * An ordering of all twelve keys for serial composition.
+ */
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ToneRow {
+	pub keys: Vec<Key>,
+}
+
+const ROW_FORMS: [RowForm; 4] = [RowForm::Prime, RowForm::Retrograde, RowForm::Inversion, RowForm::RetrogradeInversion];
+
+impl ToneRow {
+	/*
+	 * Returns None, if the keys are not all twelve keys, each of them exactly once.
+	 */
+	pub fn new(keys: &[Key]) -> Option<ToneRow> {
+		if keys.len() != ROW_LENGTH || PitchClassSet::from_keys(keys).len() != ROW_LENGTH {
+			return None;
+		}
+		Some(ToneRow { keys: keys.to_vec() })
+	}
+
+	pub fn transpose(&self, interval: Interval) -> ToneRow {
+		ToneRow { keys: self.keys.iter().map(|key| *key + interval).collect() }
+	}
+
+	pub fn retrograde(&self) -> ToneRow {
+		ToneRow { keys: self.keys.iter().rev().cloned().collect() }
+	}
+
+	/*
+	 * Mirrors every key around the first key of the row, so the inversion starts with the same key.
+	 */
+	pub fn inversion(&self) -> ToneRow {
+		let first_key = self.keys[0];
+		ToneRow { keys: self.keys.iter().map(|key| first_key - Key::to_interval(first_key, *key)).collect() }
+	}
+
+	/*
+	 * Returns the given form of the row transposed by the given number of half tones, like P3 or RI7.
+	 * The transposition counts from the first key of this row, so P0 is the row itself and I0 starts with
+	 * the same key. Retrogrades are numbered like the form they are the retrograde of, so R0 ends with the
+	 * first key of this row.
+	 */
+	pub fn form(&self, row_form: RowForm, transposition: i32) -> ToneRow {
+		let interval = Interval { halftones: transposition };
+		match row_form {
+			RowForm::Prime => self.transpose(interval),
+			RowForm::Retrograde => self.transpose(interval).retrograde(),
+			RowForm::Inversion => self.inversion().transpose(interval),
+			RowForm::RetrogradeInversion => self.inversion().transpose(interval).retrograde(),
+		}
+	}
+
+	/*
+	 * Returns the forms of the row at all twelve transpositions, starting with transposition 0.
+	 */
+	pub fn forms(&self, row_form: RowForm) -> Vec<ToneRow> {
+		(0..ROW_LENGTH as i32).map(|transposition| self.form(row_form, transposition)).collect()
+	}
+
+	/*
+	 * Returns the 12x12 matrix of the row: the rows of the matrix are the prime forms read from left to right
+	 * (and the retrograde forms from right to left), the columns the inversions read from top to bottom (and
+	 * the retrograde inversions from bottom to top). The first row is P0 and the first column I0.
+	 */
+	pub fn matrix(&self) -> Vec<Vec<Key>> {
+		let first_key = self.keys[0];
+		self.inversion().keys.iter()
+			.map(|key| self.transpose(Key::to_interval(first_key, *key)).keys)
+			.collect()
+	}
+
+	/*
+	 * Returns the transposition number of the given form for the matrix label, like 3 for P3.
+	 */
+	fn transposition_of(&self, row: &ToneRow, row_form: RowForm) -> i32 {
+		let first_key = match row_form {
+			RowForm::Prime | RowForm::Inversion => row.keys[0],
+			RowForm::Retrograde | RowForm::RetrogradeInversion => row.keys[ROW_LENGTH - 1],
+		};
+		Key::to_interval(self.keys[0], first_key).halftones
+	}
+
+	/*
+	 * Returns all forms of the row, whose first hexachord contains exactly the keys of the second hexachord
+	 * of this row, so both rows can be played at the same time without repeating a key within a hexachord.
+	 * R0 is always one of them.
+	 */
+	pub fn combinatorial_forms(&self) -> Vec<(RowForm, i32)> {
+		let first_hexachord = PitchClassSet::from_keys(&self.keys[..ROW_LENGTH / 2]);
+		let mut forms = Vec::new();
+		for row_form in ROW_FORMS.iter() {
+			for row in self.forms(*row_form) {
+				if PitchClassSet::from_keys(&row.keys[..ROW_LENGTH / 2]) == first_hexachord.complement() {
+					forms.push((*row_form, self.transposition_of(&row, *row_form)));
+				}
+			}
+		}
+		forms
+	}
+
+	/*
+	 * Returns whether the row is hexachordally combinatorial with a transposition of the given form.
+	 */
+	pub fn is_combinatorial(&self, row_form: RowForm) -> bool {
+		self.combinatorial_forms().iter().any(|(form, _)| *form == row_form)
+	}
+
+	/*
+	 * Returns whether the row is combinatorial with transpositions of all four forms.
+	 */
+	pub fn is_all_combinatorial(&self) -> bool {
+		ROW_FORMS.iter().all(|row_form| self.is_combinatorial(*row_form))
+	}
+
+	/*
+	 * Plays the row with the rhythm, one key per note of the rhythm, every key in the given octave.
+	 * The row starts again, if the rhythm has more than twelve notes.
+	 */
+	pub fn to_notes(&self, rhythm: &RhythmPattern, octave: Octave) -> Vec<Note> {
+		rhythm.notes.iter().zip(self.keys.iter().cycle()).map(|(rhythm_note, key)| {
+			let (time_point, _) = rhythm_note.get_time_points();
+			Note { time_point, duration: rhythm_note.get_duration(), pitch: Pitch::from_key_and_octave(*key, octave) }
+		}).collect()
+	}
+}
+
+impl fmt::Display for RowForm {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			RowForm::Prime => write!(f, "P"),
+			RowForm::Retrograde => write!(f, "R"),
+			RowForm::Inversion => write!(f, "I"),
+			RowForm::RetrogradeInversion => write!(f, "RI"),
+		}
+	}
+}
+
+/*
+ * Writes the keys separated by spaces.
+ */
+impl fmt::Display for ToneRow {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let keys: Vec<String> = self.keys.iter().map(|key| key.to_string()).collect();
+		write!(f, "{}", keys.join(" "))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rhythm::{BarTimeSignature, Duration, TimePoint};
+
+	fn keys(names: &str) -> Vec<Key> {
+		names.split_whitespace().map(|name| name.parse().unwrap()).collect()
+	}
+
+	fn berg_row() -> ToneRow {
+		ToneRow::new(&keys("g bb d f# a c e g# b c# eb f")).unwrap()
+	}
+
+	#[test]
+	pub fn row_forms() {
+		let row = berg_row();
+		assert_eq!(ToneRow::new(&keys("c d e")), None);
+		assert_eq!(ToneRow::new(&keys("c c d eb e f f# g g# a bb b")), None);
+
+		assert_eq!(row.form(RowForm::Prime, 0), row);
+		assert_eq!(row.form(RowForm::Prime, 2).keys[..3], keys("a c e")[..]);
+		assert_eq!(row.form(RowForm::Retrograde, 0).keys[..3], keys("f eb c#")[..]);
+		assert_eq!(row.form(RowForm::Inversion, 0).keys[..4], keys("g e c g#")[..]);
+		assert_eq!(row.form(RowForm::RetrogradeInversion, 0).keys[..3], keys("a b c#")[..]);
+		assert_eq!(row.forms(RowForm::Inversion).len(), 12);
+		assert_eq!(row.to_string(), "g a# d f# a c e g# b c# d# f");
+		assert_eq!(RowForm::RetrogradeInversion.to_string(), "RI");
+	}
+
+	#[test]
+	pub fn row_matrix() {
+		let row = berg_row();
+		let matrix = row.matrix();
+		assert_eq!(matrix.len(), 12);
+		assert_eq!(matrix[0], row.keys);
+		let first_column: Vec<Key> = matrix.iter().map(|matrix_row| matrix_row[0]).collect();
+		assert_eq!(first_column, row.inversion().keys);
+		for (index, matrix_row) in matrix.iter().enumerate() {
+			assert_eq!(matrix_row[index], Key::g());
+			assert!(ToneRow::new(matrix_row).is_some());
+		}
+		// the columns are the inversions
+		let column: Vec<Key> = matrix.iter().map(|matrix_row| matrix_row[1]).collect();
+		assert_eq!(column, row.form(RowForm::Inversion, 3).keys);
+	}
+
+	#[test]
+	pub fn combinatoriality() {
+		let chromatic_row = ToneRow::new(&keys("c c# d d# e f f# g g# a a# b")).unwrap();
+		let forms = chromatic_row.combinatorial_forms();
+		assert!(forms.contains(&(RowForm::Prime, 6)));
+		assert!(forms.contains(&(RowForm::Retrograde, 0)));
+		assert!(forms.contains(&(RowForm::Inversion, 11)));
+		assert!(forms.contains(&(RowForm::RetrogradeInversion, 5)));
+		assert!(chromatic_row.is_all_combinatorial());
+
+		let row = berg_row();
+		assert!(row.is_combinatorial(RowForm::Retrograde));
+		assert!(!row.is_all_combinatorial());
+	}
+
+	#[test]
+	pub fn row_to_notes() {
+		let rhythm = RhythmPattern::straight_rhythm_notes(BarTimeSignature::four_quarter_time(), Duration::quarter());
+		let notes = berg_row().to_notes(&rhythm, Octave { value: 3 });
+		assert_eq!(notes.len(), 4);
+		let pitches: Vec<Pitch> = notes.iter().map(|note| note.pitch).collect();
+		let expected: Vec<Pitch> = keys("g bb d f#").iter().map(|key| Pitch::from_key_and_octave(*key, Octave { value: 3 })).collect();
+		assert_eq!(pitches, expected);
+		assert!(notes[1].time_point == TimePoint::new(1, 4));
+		assert!(notes[1].duration == Duration::quarter());
+	}
+}
@@ -0,0 +1,440 @@
+use crate::keys::Key;
+use crate::pitch::{Pitch, ConcertPitch};
+use crate::parse::ParseError;
+use crate::math::neg_modulo;
+
+const NUM_HALF_TONES: usize = 12;
+const CENTS_PER_OCTAVE: f64 = 1200.0;
+
+/*
+ * Maps pitches to frequencies in Hz.
+ * Returns None for pitches, which are not covered by the tuning.
+ */
+pub trait Tuning {
+	fn frequency(&self, pitch: Pitch) -> Option<f64>;
+}
+
+/*
+ * Twelve-tone equal temperament, the tuning assumed everywhere else in this library.
+ */
+#[derive(Copy, Clone, Debug)]
+pub struct EqualTemperament {
+	pub concert_pitch: ConcertPitch,
+}
+
+/*
+ * A tuning with twelve keys per octave, which are not equally spaced.
+ * cents[i] is the distance of the key i halftones above the tonic to the tonic.
+ * The A of the concert pitch is tuned to the frequency of the concert pitch and all other pitches
+ * are tuned relative to it.
+ */
+#[derive(Clone, Debug)]
+pub struct Temperament {
+	pub tonic: Key,
+	pub cents: [f64; NUM_HALF_TONES],
+	pub concert_pitch: ConcertPitch,
+}
+
+/*
+ * A scale read from a Scala .scl file.
+ * steps contains the scale degrees 1 to n in cents, the last one is the period of the scale
+ * (usually an octave). Degree 0 is always 0 cents.
+ */
+#[derive(Clone, Debug)]
+pub struct ScalaScale {
+	pub description: String,
+	pub steps: Vec<f64>,
+}
+
+/*
+ * A keyboard mapping read from a Scala .kbm file.
+ * Maps MIDI numbers to scale degrees. A mapping of None means a linear mapping,
+ * a None entry inside the mapping is a key which is not mapped (written as x in the file).
+ */
+#[derive(Clone, Debug)]
+pub struct KeyboardMapping {
+	pub first_midi_number: i32,
+	pub last_midi_number: i32,
+	pub middle_midi_number: i32,
+	pub reference_midi_number: i32,
+	pub reference_frequency: f64,
+	pub octave_degree: i32,
+	pub mapping: Option<Vec<Option<i32>>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ScalaTuning {
+	pub scale: ScalaScale,
+	pub keyboard_mapping: KeyboardMapping,
+}
+
+fn ratio_to_cents(ratio: f64) -> f64 {
+	CENTS_PER_OCTAVE * ratio.log2()
+}
+
+fn ratios_to_cents(ratios: [(u32, u32); NUM_HALF_TONES]) -> [f64; NUM_HALF_TONES] {
+	let mut cents = [0.0; NUM_HALF_TONES];
+	for (index, (nominator, denominator)) in ratios.iter().enumerate() {
+		cents[index] = ratio_to_cents(*nominator as f64 / *denominator as f64);
+	}
+	cents
+}
+
+impl EqualTemperament {
+	pub fn new(concert_pitch: ConcertPitch) -> EqualTemperament {
+		EqualTemperament { concert_pitch }
+	}
+}
+
+impl Tuning for EqualTemperament {
+	fn frequency(&self, pitch: Pitch) -> Option<f64> {
+		Some(pitch.to_frequency(self.concert_pitch))
+	}
+}
+
+impl Temperament {
+	pub fn new(tonic: Key, cents: [f64; NUM_HALF_TONES], concert_pitch: ConcertPitch) -> Temperament {
+		Temperament { tonic, cents, concert_pitch }
+	}
+
+	/*
+	 * Five-limit just intonation on the given tonic.
+	 */
+	pub fn just_intonation(tonic: Key, concert_pitch: ConcertPitch) -> Temperament {
+		let ratios = [(1, 1), (16, 15), (9, 8), (6, 5), (5, 4), (4, 3), (45, 32), (3, 2), (8, 5), (5, 3), (9, 5), (15, 8)];
+		Temperament::new(tonic, ratios_to_cents(ratios), concert_pitch)
+	}
+
+	/*
+	 * Pythagorean tuning built from pure fifths, with the wolf fifth between the sharpened fourth and
+	 * the flattened second.
+	 */
+	pub fn pythagorean(tonic: Key, concert_pitch: ConcertPitch) -> Temperament {
+		let ratios = [(1, 1), (256, 243), (9, 8), (32, 27), (81, 64), (4, 3), (729, 512), (3, 2), (128, 81), (27, 16), (16, 9), (243, 128)];
+		Temperament::new(tonic, ratios_to_cents(ratios), concert_pitch)
+	}
+
+	/*
+	 * Quarter-comma meantone with pure major thirds, covering the fifths from the flattened third
+	 * to the sharpened fifth.
+	 */
+	pub fn quarter_comma_meantone(tonic: Key, concert_pitch: ConcertPitch) -> Temperament {
+		let fifth = ratio_to_cents(5f64.powf(0.25));
+		// number of fifths above the tonic for every key of the octave
+		let num_fifths = [0, 7, 2, -3, 4, -1, 6, 1, 8, 3, -2, 5];
+		let mut cents = [0.0; NUM_HALF_TONES];
+		for (index, n) in num_fifths.iter().enumerate() {
+			cents[index] = (*n as f64 * fifth).rem_euclid(CENTS_PER_OCTAVE);
+		}
+		Temperament::new(tonic, cents, concert_pitch)
+	}
+
+	/*
+	 * Werckmeister III, the well temperament with four fifths tempered by a quarter of the
+	 * Pythagorean comma.
+	 */
+	pub fn werckmeister_iii(tonic: Key, concert_pitch: ConcertPitch) -> Temperament {
+		let cents = [0.0, 90.225, 192.180, 294.135, 390.225, 498.045, 588.270, 696.090, 792.180, 888.270, 996.090, 1092.180];
+		Temperament::new(tonic, cents, concert_pitch)
+	}
+
+	fn absolute_cents(&self, pitch: Pitch) -> f64 {
+		let halftones_above_tonic = pitch.value - self.tonic.get_value() as i32;
+		let degree = neg_modulo(halftones_above_tonic, NUM_HALF_TONES as u32) as i32;
+		let octave = (halftones_above_tonic - degree) / NUM_HALF_TONES as i32;
+		octave as f64 * CENTS_PER_OCTAVE + self.cents[degree as usize]
+	}
+}
+
+impl Tuning for Temperament {
+	fn frequency(&self, pitch: Pitch) -> Option<f64> {
+		let cents = self.absolute_cents(pitch) - self.absolute_cents(ConcertPitch::PITCH);
+		Some(self.concert_pitch.frequency * 2f64.powf(cents / CENTS_PER_OCTAVE))
+	}
+}
+
+/*
+ * Returns the lines of a Scala file, which are not comments.
+ */
+fn scala_lines(text: &str) -> impl Iterator<Item = &str> {
+	text.lines().filter(|line| !line.starts_with('!'))
+}
+
+fn parse_scala_value<T: std::str::FromStr>(line: Option<&str>, what: &str) -> Result<T, ParseError> {
+	let line = line.ok_or_else(|| ParseError::InvalidScalaFile(format!("missing {}", what)))?;
+	let token = line.split_whitespace().next().unwrap_or("");
+	token.parse().map_err(|_| ParseError::InvalidScalaFile(format!("invalid {} \"{}\"", what, line.trim())))
+}
+
+impl ScalaScale {
+	/*
+	 * Parses the content of a Scala .scl file.
+	 * Pitch values containing a "." are cents, all other values are ratios like "3/2" or "2".
+	 */
+	pub fn from_scl(text: &str) -> Result<ScalaScale, ParseError> {
+		let mut lines = scala_lines(text);
+		let description = lines.next()
+			.ok_or_else(|| ParseError::InvalidScalaFile("missing description".to_string()))?
+			.trim().to_string();
+		let num_steps: usize = parse_scala_value(lines.next(), "number of notes")?;
+
+		let mut steps = Vec::with_capacity(num_steps);
+		for _ in 0..num_steps {
+			let line = lines.next().ok_or_else(|| ParseError::InvalidScalaFile("missing pitch value".to_string()))?;
+			let token = line.split_whitespace().next().unwrap_or("");
+			let invalid = || ParseError::InvalidScalaFile(format!("invalid pitch value \"{}\"", line.trim()));
+			let cents = if token.contains('.') {
+				token.parse().map_err(|_| invalid())?
+			} else {
+				let mut parts = token.splitn(2, '/');
+				let nominator: u64 = parts.next().unwrap_or("").parse().map_err(|_| invalid())?;
+				let denominator: u64 = parts.next().unwrap_or("1").parse().map_err(|_| invalid())?;
+				if nominator == 0 || denominator == 0 {
+					return Err(invalid());
+				}
+				ratio_to_cents(nominator as f64 / denominator as f64)
+			};
+			steps.push(cents);
+		}
+		if steps.is_empty() {
+			return Err(ParseError::InvalidScalaFile("scale has no notes".to_string()));
+		}
+
+		Ok(ScalaScale { description, steps })
+	}
+
+	/*
+	 * Returns the distance of the given scale degree to degree 0 in cents. Degrees outside of
+	 * 0 to n are continued with the period of the scale.
+	 */
+	pub fn degree_to_cents(&self, degree: i32) -> f64 {
+		let num_steps = self.steps.len() as i32;
+		let base_degree = neg_modulo(degree, num_steps as u32) as i32;
+		let num_periods = (degree - base_degree) / num_steps;
+		let period = self.steps[self.steps.len() - 1];
+		let base_cents = if base_degree == 0 { 0.0 } else { self.steps[base_degree as usize - 1] };
+		num_periods as f64 * period + base_cents
+	}
+}
+
+impl KeyboardMapping {
+	/*
+	 * Maps every MIDI number to the next scale degree, with degree 0 on the middle MIDI number.
+	 */
+	pub fn linear(middle_midi_number: i32, reference_midi_number: i32, reference_frequency: f64) -> KeyboardMapping {
+		KeyboardMapping {
+			first_midi_number: 0,
+			last_midi_number: 127,
+			middle_midi_number,
+			reference_midi_number,
+			reference_frequency,
+			octave_degree: 0,
+			mapping: None,
+		}
+	}
+
+	/*
+	 * Parses the content of a Scala .kbm file.
+	 */
+	pub fn from_kbm(text: &str) -> Result<KeyboardMapping, ParseError> {
+		let mut lines = scala_lines(text).filter(|line| !line.trim().is_empty());
+		let map_size: usize = parse_scala_value(lines.next(), "map size")?;
+		let first_midi_number = parse_scala_value(lines.next(), "first MIDI note")?;
+		let last_midi_number = parse_scala_value(lines.next(), "last MIDI note")?;
+		let middle_midi_number = parse_scala_value(lines.next(), "middle note")?;
+		let reference_midi_number = parse_scala_value(lines.next(), "reference note")?;
+		let reference_frequency = parse_scala_value(lines.next(), "reference frequency")?;
+		let octave_degree = parse_scala_value(lines.next(), "formal octave degree")?;
+
+		let mapping = if map_size == 0 {
+			None
+		} else {
+			let mut mapping = Vec::with_capacity(map_size);
+			for line in lines.take(map_size) {
+				if line.trim().starts_with('x') {
+					mapping.push(None);
+				} else {
+					mapping.push(Some(parse_scala_value(Some(line), "mapping entry")?));
+				}
+			}
+			mapping.resize(map_size, None);
+			Some(mapping)
+		};
+
+		Ok(KeyboardMapping {
+			first_midi_number,
+			last_midi_number,
+			middle_midi_number,
+			reference_midi_number,
+			reference_frequency,
+			octave_degree,
+			mapping,
+		})
+	}
+
+	/*
+	 * Returns the scale degree of the given MIDI number or None, if the MIDI number is not mapped.
+	 */
+	pub fn get_degree(&self, midi_number: i32) -> Option<i32> {
+		if midi_number < self.first_midi_number || midi_number > self.last_midi_number {
+			return None;
+		}
+		let offset = midi_number - self.middle_midi_number;
+		match &self.mapping {
+			None => Some(offset),
+			Some(mapping) => {
+				let index = neg_modulo(offset, mapping.len() as u32) as i32;
+				let num_repetitions = (offset - index) / mapping.len() as i32;
+				mapping[index as usize].map(|degree| degree + num_repetitions * self.octave_degree)
+			}
+		}
+	}
+}
+
+impl ScalaTuning {
+	/*
+	 * Uses the scale with a linear keyboard mapping, which puts degree 0 on the middle C and tunes
+	 * the middle C like in equal temperament with the given concert pitch.
+	 */
+	pub fn new(scale: ScalaScale, concert_pitch: ConcertPitch) -> ScalaTuning {
+		let middle_c = Pitch { value: 39 };
+		let midi_number = middle_c.to_midi_number().unwrap() as i32;
+		let keyboard_mapping = KeyboardMapping::linear(midi_number, midi_number, middle_c.to_frequency(concert_pitch));
+		ScalaTuning { scale, keyboard_mapping }
+	}
+
+	pub fn with_keyboard_mapping(scale: ScalaScale, keyboard_mapping: KeyboardMapping) -> ScalaTuning {
+		ScalaTuning { scale, keyboard_mapping }
+	}
+
+	fn midi_number_to_cents(&self, midi_number: i32) -> Option<f64> {
+		self.keyboard_mapping.get_degree(midi_number).map(|degree| self.scale.degree_to_cents(degree))
+	}
+}
+
+impl Tuning for ScalaTuning {
+	fn frequency(&self, pitch: Pitch) -> Option<f64> {
+		// MIDI numbers outside of 0 to 127 are allowed, as long as they are inside the mapped range
+		let midi_number = pitch.value + 21;
+		let cents = self.midi_number_to_cents(midi_number)?;
+		let reference_cents = self.midi_number_to_cents(self.keyboard_mapping.reference_midi_number)?;
+		Some(self.keyboard_mapping.reference_frequency * 2f64.powf((cents - reference_cents) / CENTS_PER_OCTAVE))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_close(value: f64, expected: f64) {
+		assert!((value - expected).abs() < 1e-3, "{} != {}", value, expected);
+	}
+
+	const MIDDLE_C: Pitch = Pitch { value: 39 };
+	const CONCERT_A: Pitch = Pitch { value: 48 };
+
+	#[test]
+	pub fn equal_temperament() {
+		let tuning = EqualTemperament::new(ConcertPitch::standard());
+		assert_close(tuning.frequency(CONCERT_A).unwrap(), 440.0);
+		assert_close(tuning.frequency(MIDDLE_C).unwrap(), 261.6256);
+	}
+
+	#[test]
+	pub fn just_intonation() {
+		let tuning = Temperament::just_intonation(Key::c(), ConcertPitch::standard());
+		assert_close(tuning.frequency(CONCERT_A).unwrap(), 440.0);
+		// the major sixth c - a is 5/3
+		assert_close(tuning.frequency(MIDDLE_C).unwrap(), 264.0);
+		// the fifth c - g is 3/2
+		assert_close(tuning.frequency(Pitch { value: 46 }).unwrap(), 396.0);
+		assert_close(tuning.frequency(Pitch { value: 27 }).unwrap(), 132.0);
+	}
+
+	#[test]
+	pub fn historical_temperaments() {
+		let pythagorean = Temperament::pythagorean(Key::c(), ConcertPitch::standard());
+		// the major sixth c - a is 27/16
+		assert_close(pythagorean.frequency(MIDDLE_C).unwrap(), 440.0 * 16.0 / 27.0);
+
+		let meantone = Temperament::quarter_comma_meantone(Key::c(), ConcertPitch::standard());
+		// the major third c - e is pure
+		let c = meantone.frequency(MIDDLE_C).unwrap();
+		let e = meantone.frequency(Pitch { value: 43 }).unwrap();
+		assert_close(e / c, 1.25);
+
+		let werckmeister = Temperament::werckmeister_iii(Key::c(), ConcertPitch::baroque());
+		assert_close(werckmeister.frequency(CONCERT_A).unwrap(), 415.0);
+		assert_close(werckmeister.frequency(Pitch { value: 60 }).unwrap(), 830.0);
+	}
+
+	const SCL: &str = "! meanquar.scl
+!
+1/4-comma meantone scale. Pietro Aaron's temperament (1523)
+ 12
+!
+ 76.04900
+ 193.15686
+ 310.26471
+ 5/4
+ 503.42157
+ 579.47057
+ 696.57843
+ 25/16
+ 889.73529
+ 1006.84314
+ 1082.89214
+ 2/1
+";
+
+	#[test]
+	pub fn scala_scale() {
+		let scale = ScalaScale::from_scl(SCL).unwrap();
+		assert_eq!(scale.description, "1/4-comma meantone scale. Pietro Aaron's temperament (1523)");
+		assert_eq!(scale.steps.len(), 12);
+		assert_close(scale.degree_to_cents(4), 386.3137);
+		assert_close(scale.degree_to_cents(12), 1200.0);
+		assert_close(scale.degree_to_cents(-1), -117.1079);
+
+		let tuning = ScalaTuning::new(scale, ConcertPitch::standard());
+		assert_close(tuning.frequency(MIDDLE_C).unwrap(), 261.6256);
+		assert_close(tuning.frequency(Pitch { value: 43 }).unwrap(), 261.6256 * 1.25);
+
+		assert!(ScalaScale::from_scl("description\n2\n100.0\n").is_err());
+		assert!(ScalaScale::from_scl("description\n1\n0/1\n").is_err());
+	}
+
+	#[test]
+	pub fn scala_keyboard_mapping() {
+		let kbm = "! white keys only
+7
+0
+127
+60
+69
+440.0
+12
+0
+2
+4
+5
+7
+9
+11
+";
+		let keyboard_mapping = KeyboardMapping::from_kbm(kbm).unwrap();
+		assert_eq!(keyboard_mapping.get_degree(60), Some(0));
+		assert_eq!(keyboard_mapping.get_degree(62), Some(4));
+		assert_eq!(keyboard_mapping.get_degree(67), Some(12));
+		assert_eq!(keyboard_mapping.get_degree(59), Some(-1));
+
+		let tuning = ScalaTuning::with_keyboard_mapping(ScalaScale::from_scl(SCL).unwrap(), keyboard_mapping);
+		// MIDI number 65 is mapped to degree 9, the reference note 69 to degree 16
+		assert_close(tuning.frequency(Pitch { value: 44 }).unwrap(), 440.0 * 2f64.powf((889.73529 - 1200.0 - 386.31371) / 1200.0));
+		assert_close(tuning.frequency(Pitch { value: 48 }).unwrap(), 440.0);
+
+		let with_unmapped_key = "1\n0\n127\n60\n60\n261.0\n1\nx\n";
+		let keyboard_mapping = KeyboardMapping::from_kbm(with_unmapped_key).unwrap();
+		assert_eq!(keyboard_mapping.get_degree(60), None);
+	}
+}
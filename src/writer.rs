@@ -5,14 +5,33 @@ use rimd::{SMFBuilder, MidiMessage, MetaEvent, SMFWriter};
 
 use crate::rhythm::{TimePoint, RhythmNote};
 use crate::note::Note;
+use crate::key_signature::{KeySignature, Mode};
 
 pub struct MidiWriter {
 	notes: Vec<Note>,
+	key_signature: Option<KeySignature>,
+}
+
+/*
+ * Returns the accidentals (as two's complement, so flats are negative) and 1 for minor or 0 for major, as
+ * written in the MIDI key signature event.
+ */
+fn key_signature_data(key_signature: KeySignature) -> (u8, u8) {
+	let minor = if key_signature.mode == Mode::Minor { 1 } else { 0 };
+	(key_signature.accidentals as u8, minor)
 }
 
 impl MidiWriter {
 	pub fn new() -> MidiWriter {
-		MidiWriter { notes: Vec::new() }
+		MidiWriter { notes: Vec::new(), key_signature: None }
+	}
+
+	/*
+	 * MIDI only knows major and minor key signatures, so all other modes are written as the major key
+	 * signature with the same accidentals.
+	 */
+	pub fn set_key_signature(&mut self, key_signature: KeySignature) {
+		self.key_signature = Some(key_signature);
 	}
 
 	pub fn add_notes<'a, I>(&mut self, notes: I)
@@ -30,6 +49,10 @@ impl MidiWriter {
 		// express notes as micro_timing
 		let mut smf_builder: SMFBuilder = SMFBuilder::new();
 		smf_builder.add_track();
+		if let Some(key_signature) = self.key_signature {
+			let (accidentals, minor) = key_signature_data(key_signature);
+			smf_builder.add_meta_abs(0, 0, MetaEvent::key_signature(accidentals, minor));
+		}
 		let mut last_time_point: TimePoint = TimePoint::new(0, 1);
 		for note in self.notes.iter() {
 			if let Some(pitch) = note.pitch.to_midi_number() {
@@ -67,6 +90,7 @@ mod tests {
 	use crate::rhythm::Duration;
 	use crate::keys::Key;
 	use crate::pitch::{Pitch, Octave};
+	use crate::spelled_key::{Letter, SpelledKey};
 
 	#[test]
 	pub fn test_writing() {
@@ -111,4 +135,14 @@ mod tests {
 		midi_writer.add_notes(notes.iter());
 		midi_writer.write(Path::new("./test.mid"));
 	}
+
+	#[test]
+	pub fn test_key_signature() {
+		let mut midi_writer = MidiWriter::new();
+		// g-sharp major has eight sharps, so it is written as a-flat major
+		midi_writer.set_key_signature(KeySignature::new(SpelledKey::new(Letter::G, 1), Mode::Major));
+		assert_eq!(key_signature_data(midi_writer.key_signature.unwrap()), (-4i8 as u8, 0));
+		assert_eq!(key_signature_data(KeySignature::from_accidentals(3, Mode::Minor)), (3, 1));
+		assert_eq!(key_signature_data(KeySignature::from_accidentals(7, Mode::Dorian)), (7, 0));
+	}
 }